@@ -3,10 +3,8 @@ use discord_ipc_rust::models::receive::{
     ReceivedItem, commands::ReturnedCommand, events::ReturnedEvent,
 };
 use discord_ipc_rust::models::send::commands::SetVoiceSettingsArgs;
-use discord_ipc_rust::models::send::{
-    commands::{AuthorizeArgs, SentCommand},
-    events::SubscribeableEvent,
-};
+use discord_ipc_rust::models::send::{commands::SentCommand, events::SubscribeableEvent};
+use discord_ipc_rust::models::shared::Scope;
 
 #[tokio::main]
 async fn main() {
@@ -22,14 +20,12 @@ async fn example() -> discord_ipc_rust::Result<()> {
         dotenv::var("CLIENT_ID").expect("You must set the CLIENT_ID environment variable");
     let client_secret = dotenv::var("CLIENT_SECRET").ok();
     let access_token = dotenv::var("ACCESS_TOKEN").ok();
-    if (client_secret.is_none() && access_token.is_none())
-        || (client_secret.is_some() && access_token.is_some())
-    {
+    if client_secret.is_some() && access_token.is_some() {
         panic!("You must only set either the CLIENT_SECRET or ACCESS_TOKEN environment variable");
     }
 
     // Connect to the Discord RPC server
-    let (mut rpc, user) = DiscordIpcClient::create(client_id.clone()).await?;
+    let (rpc, user) = DiscordIpcClient::create(client_id.clone()).await?;
     println!("Connected to Discord as {}", user.username);
 
     if let Some(access_token) = access_token {
@@ -38,7 +34,7 @@ async fn example() -> discord_ipc_rust::Result<()> {
             "Access token environment variable present: demonstrating some features available to an authenticated client..."
         );
         rpc.authenticate(access_token).await?;
-        rpc.setup_event_handler(handle_message).await; // setup_event_handler must be called after authenticate!
+        rpc.setup_event_handler(handle_message);
 
         // Ask Discord to notify us of updates to the voice settings and for the currently selected voice channel
         rpc.emit_command(&SentCommand::Subscribe(
@@ -67,43 +63,32 @@ async fn example() -> discord_ipc_rust::Result<()> {
             "Access token environment variable not present: demonstrating the authorization process..."
         );
 
-        rpc.setup_event_handler(|item| {
-            let code = match &item {
-                ReceivedItem::Command(command) => match &**command {
-                    ReturnedCommand::Authorize { code } => code.clone(),
-                    _ => return handle_message(item),
-                },
-                _ => return handle_message(item),
-            };
-            println!(
-                "Received authorization code: {}, exchanging for access token using client secret",
-                code
-            );
-            tokio::spawn(async move {
-                if let Err(error) = exchange_code_for_token(&code).await {
-                    eprintln!(
-                        "Failed to exchange authorization code for access token: {}",
-                        error
-                    );
-                }
-                std::process::exit(0);
-            });
-        })
-        .await;
-
-        rpc.emit_command(&SentCommand::Authorize(AuthorizeArgs {
-            client_id,
-            scopes: vec!["rpc".to_owned(), "identify".to_owned()],
-            rpc_token: None,
-            username: None,
-        }))
-        .await?;
-        println!("Requested authorization prompt to obtain OAuth2 authorization code");
+        // Your app must have this redirect URI configured in the Discord Developer Portal for authentication to work
+        // Without a client secret, PKCE is used instead, which requires your app to be a public client
+        let redirect_uri =
+            dotenv::var("REDIRECT_URI").unwrap_or_else(|_| "http://localhost".to_owned());
+        println!("Requesting authorization prompt to obtain OAuth2 authorization code...");
+        let (data, token) = rpc
+            .authorize_and_authenticate(
+                vec![
+                    Scope::Rpc,
+                    Scope::Identify,
+                    Scope::RpcVoiceRead,
+                    Scope::RpcVoiceWrite,
+                ],
+                client_secret.as_deref(),
+                &redirect_uri,
+            )
+            .await?;
 
-        loop {
-            // Keep the program alive to listen for authorization response
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-        }
+        println!(
+            "Authenticated as {}, obtained access token: {}",
+            data.user.username, token.access_token
+        );
+        println!(
+            "You can now set this value for the ACCESS_TOKEN environment variable to use it in the example"
+        );
+        Ok(())
     }
 }
 
@@ -128,54 +113,17 @@ fn handle_message(item: ReceivedItem) {
             ReturnedCommand::SetVoiceSettings(_) => println!("Voice settings update succeeded"),
             _ => println!("Received other command response: {:?}", command),
         },
+        ReceivedItem::Reconnecting { attempt } => {
+            println!("Reconnecting to Discord (attempt {})", attempt)
+        }
+        ReceivedItem::Reconnected(user) => println!("Reconnected to Discord as {}", user.username),
+        ReceivedItem::Lagged(skipped) => eprintln!("Fell behind and skipped {} values", skipped),
+        ReceivedItem::Closed(reason) => {
+            eprintln!("Discord closed the connection: {}", reason.message)
+        }
         ReceivedItem::SocketClosed => {
             eprintln!("Discord IPC socket closed");
             std::process::exit(0);
         }
     }
 }
-
-// Exchange an OAuth2 authorization code for an access token using the client secret
-// Your app must have at least one redirect URI configured in the Discord Developer Portal for authentication to work
-async fn exchange_code_for_token(code: &str) -> Result<(), reqwest::Error> {
-    let params = [
-        ("client_id", &dotenv::var("CLIENT_ID").unwrap()[..]),
-        ("client_secret", &dotenv::var("CLIENT_SECRET").unwrap()),
-        ("grant_type", "authorization_code"),
-        ("code", code),
-    ];
-    let client = reqwest::Client::new();
-
-    let response = client
-        .post("https://discord.com/api/v10/oauth2/token")
-        .form(&params)
-        .send()
-        .await?;
-    let value = serde_json::from_str::<serde_json::Value>(&response.text().await?)
-        .ok()
-        .and_then(|v| v.as_object().cloned())
-        .unwrap();
-
-    if let Some(access_token) = value.get("access_token").and_then(|v| v.as_str()) {
-        println!("Obtained access token: {}", access_token);
-        println!(
-            "You can now set this value for the ACCESS_TOKEN environment variable to use it in the example"
-        );
-    } else if let Some(error) = value.get("error").and_then(|v| v.as_str()) {
-        if let Some(error_description) = value.get("error_description").and_then(|v| v.as_str()) {
-            eprintln!(
-                "Failed to obtain access token: {}: {}",
-                error, error_description
-            );
-        } else {
-            eprintln!("Failed to obtain access token: {}", error);
-        }
-    } else {
-        eprintln!(
-            "Failed to obtain access token: unexpected response {:?}",
-            value
-        );
-    }
-
-    Ok(())
-}
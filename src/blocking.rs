@@ -0,0 +1,334 @@
+//! A synchronous client for applications that do not run an async runtime
+
+use crate::builder::ConnectOptions;
+use crate::frame::{Frame, FrameCodec, OpCode};
+use crate::models::receive::{
+    ReceivedItem,
+    commands::{AuthenticateData, ReturnedCommand, SetActivityData},
+    events::{CloseData, ReturnedEvent},
+};
+use crate::models::send::{
+    commands::{AuthenticateArgs, AuthorizeArgs, SentCommand, SetActivityArgs},
+    events::SubscribeableEvent,
+};
+use crate::models::shared::{Scope, User, activity::Activity};
+use crate::utils::{check_scopes, create_packet_json, get_pipe_path};
+use crate::{DiscordRPCError, Result, TimeoutPhase};
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+#[cfg(target_family = "unix")]
+type Socket = std::os::unix::net::UnixStream;
+#[cfg(target_family = "windows")]
+type Socket = std::fs::File;
+
+/// A client that blocks the calling thread while it waits for the RPC server
+///
+/// Values that are not responses to a request are queued while a request waits for its response and are returned by
+/// [`DiscordIpcClient::recv`]. Timeouts are only supported on Unix, Windows named pipes wait indefinitely
+pub struct DiscordIpcClient {
+    pub client_id: String,
+    socket: Socket,
+    codec: FrameCodec,
+    /// Bytes read from the socket that do not yet form a complete frame
+    buffer: Vec<u8>,
+    /// Values received while waiting for a response
+    queued: VecDeque<ReceivedItem>,
+    request_timeout: Option<Duration>,
+    /// The scopes granted by the last successful AUTHENTICATE
+    scopes: Option<Vec<Scope>>,
+    check_scopes: bool,
+}
+
+impl DiscordIpcClient {
+    /// Returns a newly constructed client and the active Discord user
+    pub fn create(client_id: String) -> Result<(DiscordIpcClient, User)> {
+        Self::connect(client_id, &ConnectOptions::default())
+    }
+
+    /// Returns a client connected to the socket selected by the options and the active Discord user
+    /// Opening a local socket does not block, so only the handshake timeout of the options applies
+    pub fn connect(
+        client_id: String,
+        options: &ConnectOptions,
+    ) -> Result<(DiscordIpcClient, User)> {
+        let path = get_pipe_path(options).ok_or(DiscordRPCError::PipeNotFound)?;
+        let socket = open_socket(&path).map_err(|_| DiscordRPCError::CouldNotConnect)?;
+
+        let mut client = Self {
+            client_id,
+            socket,
+            codec: FrameCodec::new(options.max_frame_size),
+            buffer: Vec::new(),
+            queued: VecDeque::new(),
+            request_timeout: None,
+            scopes: None,
+            check_scopes: true,
+        };
+        let user = client.handshake(options.handshake_timeout)?;
+        Ok((client, user))
+    }
+
+    /// How long to wait for the response to each command, which is indefinitely by default since
+    /// commands such as AUTHORIZE wait for the user to respond in Discord
+    pub fn set_request_timeout(&mut self, timeout: Option<Duration>) {
+        self.request_timeout = timeout;
+    }
+
+    /// Whether to reject commands that need OAuth2 scopes which were not granted before sending them, which is
+    /// enabled by default. Disable this if the scopes a command needs differ from those that are documented
+    pub fn set_check_scopes(&mut self, check: bool) {
+        self.check_scopes = check;
+    }
+
+    /// The OAuth2 scopes granted by the last successful AUTHENTICATE, or None before authenticating
+    pub fn granted_scopes(&self) -> Option<&[Scope]> {
+        self.scopes.as_deref()
+    }
+
+    /// Request authorization from the user, returning the OAuth2 authorization code once they accept
+    pub fn authorize(&mut self, args: AuthorizeArgs) -> Result<String> {
+        match self.request(SentCommand::Authorize(args))? {
+            ReturnedCommand::Authorize { code } => Ok(code),
+            _ => Err(DiscordRPCError::UnexpectedResponse),
+        }
+    }
+
+    /// Authenticate with the RPC server using an OAuth2 access token
+    pub fn authenticate(&mut self, access_token: String) -> Result<AuthenticateData> {
+        let command = SentCommand::Authenticate(AuthenticateArgs { access_token });
+        match self.request(command)? {
+            ReturnedCommand::Authenticate(data) => Ok(data),
+            _ => Err(DiscordRPCError::UnexpectedResponse),
+        }
+    }
+
+    /// Set the Rich Presence activity of the user, returning the activity as displayed by Discord
+    pub fn set_activity(&mut self, activity: Activity) -> Result<Option<SetActivityData>> {
+        self.send_activity(Some(activity))
+    }
+
+    /// Clear the Rich Presence activity of the user
+    pub fn clear_activity(&mut self) -> Result<()> {
+        self.send_activity(None)?;
+        Ok(())
+    }
+
+    fn send_activity(&mut self, activity: Option<Activity>) -> Result<Option<SetActivityData>> {
+        let command = SentCommand::SetActivity(Box::new(SetActivityArgs {
+            pid: std::process::id(),
+            activity,
+        }));
+        match self.request(command)? {
+            ReturnedCommand::SetActivity(data) => Ok(data.map(|data| *data)),
+            _ => Err(DiscordRPCError::UnexpectedResponse),
+        }
+    }
+
+    /// Subscribe to an event, whose payloads are then returned by [`DiscordIpcClient::recv`]
+    pub fn subscribe(&mut self, event: SubscribeableEvent) -> Result<()> {
+        self.request(SentCommand::Subscribe(event))?;
+        Ok(())
+    }
+
+    /// Unsubscribe from an event
+    pub fn unsubscribe(&mut self, event: SubscribeableEvent) -> Result<()> {
+        self.request(SentCommand::Unsubscribe(event))?;
+        Ok(())
+    }
+
+    /// Send a command to the RPC server and wait for the response carrying the same nonce
+    /// Error responses are returned as [`DiscordRPCError::Rpc`], and commands that need scopes which were not
+    /// granted fail with [`DiscordRPCError::MissingScope`] before they are sent
+    pub fn request(&mut self, command: SentCommand) -> Result<ReturnedCommand> {
+        let mut command_json = command.to_json()?;
+        if self.check_scopes {
+            check_scopes(&command, &command_json, self.scopes.as_deref())?;
+        }
+        let (nonce, json_string) = create_packet_json(&mut command_json)?;
+        self.send(json_string.as_bytes(), OpCode::Frame)?;
+
+        let deadline = self.request_timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let Some(frame) = self.recv_data_frame(deadline)? else {
+                return Err(DiscordRPCError::Timeout {
+                    phase: TimeoutPhase::Request,
+                    after: self.request_timeout.unwrap_or_default(),
+                });
+            };
+
+            let (frame_nonce, item) = frame.parse_item();
+            if frame_nonce.as_deref() != Some(nonce.as_str()) {
+                if let Some(item) = deserialize_item(&frame, item) {
+                    self.queued.push_back(item);
+                }
+                continue;
+            }
+
+            return match item? {
+                ReceivedItem::Command(command) => {
+                    if let ReturnedCommand::Authenticate(data) = &*command {
+                        self.scopes = Some(data.scopes.clone());
+                    }
+                    Ok(*command)
+                }
+                ReceivedItem::Event(event) => match *event {
+                    ReturnedEvent::Error(error) => Err(DiscordRPCError::Rpc {
+                        code: error.code,
+                        message: error.message,
+                    }),
+                    _ => Err(DiscordRPCError::UnexpectedResponse),
+                },
+                _ => Err(DiscordRPCError::UnexpectedResponse),
+            };
+        }
+    }
+
+    /// Wait for the next value that is not a response to a request
+    /// A CLOSE frame from the RPC server is returned as [`DiscordRPCError::Closed`]
+    pub fn recv(&mut self) -> Result<ReceivedItem> {
+        loop {
+            if let Some(item) = self.next_item(None)? {
+                return Ok(item);
+            }
+        }
+    }
+
+    /// Like [`DiscordIpcClient::recv`], but returns None if no value arrives within the timeout
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<ReceivedItem>> {
+        self.next_item(Some(Instant::now() + timeout))
+    }
+
+    fn next_item(&mut self, deadline: Option<Instant>) -> Result<Option<ReceivedItem>> {
+        if let Some(item) = self.queued.pop_front() {
+            return Ok(Some(item));
+        }
+        while let Some(frame) = self.recv_data_frame(deadline)? {
+            if let Some(item) = deserialize_item(&frame, frame.parse_item().1) {
+                return Ok(Some(item));
+            }
+        }
+        Ok(None)
+    }
+
+    fn handshake(&mut self, timeout: Option<Duration>) -> Result<User> {
+        let payload = json!({ "v": 1, "client_id": self.client_id }).to_string();
+        self.send(payload.as_bytes(), OpCode::Handshake)?;
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let Some(frame) = self.recv_data_frame(deadline)? else {
+            return Err(DiscordRPCError::Timeout {
+                phase: TimeoutPhase::Handshake,
+                after: timeout.unwrap_or_default(),
+            });
+        };
+        match frame.parse()? {
+            ReturnedEvent::Ready(data) => Ok(data.user),
+            _ => Err(DiscordRPCError::CouldNotConnect),
+        }
+    }
+
+    fn send(&mut self, payload: &[u8], opcode: OpCode) -> Result<()> {
+        let mut packet = Vec::new();
+        self.codec.encode(opcode, payload, &mut packet)?;
+        self.socket.write_all(&packet)?;
+        Ok(())
+    }
+
+    /// Receives the next FRAME, answering pings along the way
+    /// Returns None once the deadline passes
+    fn recv_data_frame(&mut self, deadline: Option<Instant>) -> Result<Option<Frame>> {
+        loop {
+            let frame = match self.recv_frame(deadline) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return Ok(None),
+                Err(DiscordRPCError::UnknownOpCode(opcode)) => {
+                    eprintln!("Ignoring frame with unknown opcode {}", opcode);
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+
+            match frame.opcode {
+                OpCode::Frame => return Ok(Some(frame)),
+                OpCode::Ping => self.send(&frame.payload, OpCode::Pong)?,
+                OpCode::Close => {
+                    let data = frame.parse::<CloseData>()?;
+                    return Err(DiscordRPCError::Closed {
+                        code: data.code,
+                        message: data.message,
+                    });
+                }
+                OpCode::Handshake | OpCode::Pong => {}
+            }
+        }
+    }
+
+    /// Receives the next complete frame, keeping partial frames buffered so that timeouts never lose data
+    fn recv_frame(&mut self, deadline: Option<Instant>) -> Result<Option<Frame>> {
+        loop {
+            if let Some(frame) = self.codec.decode(&mut self.buffer)? {
+                return Ok(Some(frame));
+            }
+
+            let timeout = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => Some(remaining),
+                    _ => return Ok(None),
+                },
+                None => None,
+            };
+            #[cfg(target_family = "unix")]
+            self.socket.set_read_timeout(timeout)?;
+            #[cfg(target_family = "windows")]
+            let _ = timeout;
+
+            let mut chunk = [0u8; 4096];
+            match self.socket.read(&mut chunk) {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None);
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+}
+
+#[cfg(target_family = "unix")]
+fn open_socket(path: &std::path::Path) -> io::Result<Socket> {
+    Socket::connect(path)
+}
+
+#[cfg(target_family = "windows")]
+fn open_socket(path: &std::path::Path) -> io::Result<Socket> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+}
+
+fn deserialize_item(frame: &Frame, item: Result<ReceivedItem>) -> Option<ReceivedItem> {
+    match item {
+        Ok(item) => Some(item),
+        Err(error) => {
+            eprintln!(
+                "Failed to deserialize payload {}: {}",
+                String::from_utf8_lossy(&frame.payload),
+                error
+            );
+            None
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::frame::DEFAULT_MAX_FRAME_SIZE;
+#[cfg(feature = "oauth")]
+use crate::oauth::{DEFAULT_REFRESH_MARGIN, HttpTokenExchange, TokenExchange, TokenStore};
+#[cfg(feature = "async")]
+use crate::{
+    Result,
+    ipc::DiscordIpcClient,
+    models::shared::User,
+    reconnect::ReconnectConfig,
+    transport::{DefaultTransport, Transport},
+};
+
+use std::path::PathBuf;
+use std::time::Duration;
+#[cfg(feature = "async")]
+use std::{fmt, sync::Arc};
+
+/// Environment variable that overrides the discovered pipe path, unless an explicit path is configured
+pub const PIPE_PATH_ENV_VAR: &str = "DISCORD_IPC_PATH";
+
+/// Controls which IPC socket (named pipe on Windows) the client connects to
+///
+/// Without options, the client connects to the socket with the lowest index among the platform's default
+/// locations, unless the [`PIPE_PATH_ENV_VAR`] environment variable is set. Connecting and the handshake each
+/// time out after 5 seconds by default
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    /// Connect to exactly this socket, ignoring the environment variable and the search directories
+    pub pipe_path: Option<PathBuf>,
+    /// Only consider `discord-ipc-N` sockets with this index, which tells apart simultaneously running clients such as Stable and Canary
+    pub instance: Option<u8>,
+    /// Directories searched for `discord-ipc-N` sockets before the platform's default locations
+    pub search_dirs: Vec<PathBuf>,
+    /// How long to wait for the socket to open, or None to wait indefinitely
+    pub connect_timeout: Option<Duration>,
+    /// How long to wait for the READY event after sending the handshake, or None to wait indefinitely
+    pub handshake_timeout: Option<Duration>,
+    /// The largest frame payload accepted from the RPC server, in bytes
+    pub max_frame_size: usize,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self {
+            pipe_path: None,
+            instance: None,
+            search_dirs: Vec::new(),
+            connect_timeout: Some(Duration::from_secs(5)),
+            handshake_timeout: Some(Duration::from_secs(5)),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+        }
+    }
+}
+
+/// Configures and connects a [`DiscordIpcClient`]
+#[cfg(feature = "async")]
+#[derive(Clone)]
+pub struct ClientBuilder {
+    pub(crate) client_id: String,
+    pub(crate) options: ConnectOptions,
+    pub(crate) reconnect: Option<ReconnectConfig>,
+    pub(crate) request_timeout: Option<Duration>,
+    pub(crate) close_timeout: Option<Duration>,
+    pub(crate) clear_activity_on_close: bool,
+    pub(crate) check_scopes: bool,
+    pub(crate) transport: Arc<dyn Transport>,
+    #[cfg(feature = "oauth")]
+    pub(crate) token_exchange: Arc<dyn TokenExchange>,
+    #[cfg(feature = "oauth")]
+    pub(crate) token_store: Option<Arc<dyn TokenStore>>,
+    #[cfg(feature = "oauth")]
+    pub(crate) refresh_margin: Duration,
+}
+
+#[cfg(feature = "async")]
+impl ClientBuilder {
+    pub fn new(client_id: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            options: ConnectOptions::default(),
+            reconnect: None,
+            request_timeout: None,
+            close_timeout: Some(Duration::from_secs(5)),
+            clear_activity_on_close: false,
+            check_scopes: true,
+            transport: Arc::new(DefaultTransport::default()),
+            #[cfg(feature = "oauth")]
+            token_exchange: Arc::new(HttpTokenExchange::default()),
+            #[cfg(feature = "oauth")]
+            token_store: None,
+            #[cfg(feature = "oauth")]
+            refresh_margin: DEFAULT_REFRESH_MARGIN,
+        }
+    }
+
+    /// Replace all connection options at once
+    pub fn connect_options(mut self, options: ConnectOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Connect to exactly this socket instead of searching for one
+    pub fn pipe_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.pipe_path = Some(path.into());
+        self
+    }
+
+    /// Only connect to the `discord-ipc-N` socket with this index
+    pub fn instance(mut self, index: u8) -> Self {
+        self.options.instance = Some(index);
+        self
+    }
+
+    /// Search this directory for sockets before the platform's default locations
+    pub fn search_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.options.search_dirs.push(dir.into());
+        self
+    }
+
+    /// How long to wait for the socket to open, or None to wait indefinitely
+    pub fn connect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.options.connect_timeout = timeout;
+        self
+    }
+
+    /// How long to wait for the handshake to be sent and answered with the READY event, or None to wait indefinitely
+    pub fn handshake_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.options.handshake_timeout = timeout;
+        self
+    }
+
+    /// How long to wait for each command to be sent and answered, or None to wait indefinitely, which is the
+    /// default since commands such as AUTHORIZE wait for the user to respond in Discord
+    pub fn request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// The largest frame payload accepted from the RPC server, in bytes
+    pub fn max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.options.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Automatically reconnect whenever the connection is lost, see [`DiscordIpcClient::enable_reconnect`]
+    pub fn reconnect(mut self, config: ReconnectConfig) -> Self {
+        self.reconnect = Some(config);
+        self
+    }
+
+    /// How long [`DiscordIpcClient::close`] waits for the activity to be cleared and for the RPC server to
+    /// close the connection, each, or None to wait indefinitely. Defaults to 5 seconds
+    pub fn close_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.close_timeout = timeout;
+        self
+    }
+
+    /// Clear the activity in [`DiscordIpcClient::close`] before closing the connection, which returns the error if
+    /// the activity could not be cleared
+    pub fn clear_activity_on_close(mut self, clear: bool) -> Self {
+        self.clear_activity_on_close = clear;
+        self
+    }
+
+    /// Whether to reject commands that need OAuth2 scopes which were not granted before sending them, which is
+    /// enabled by default. Disable this if the scopes a command needs differ from those that are documented
+    pub fn check_scopes(mut self, check: bool) -> Self {
+        self.check_scopes = check;
+        self
+    }
+
+    /// Run the client on this transport instead of the [`DefaultTransport`]
+    pub fn transport(mut self, transport: impl Transport) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Exchange authorization codes for access tokens with this instead of the [`HttpTokenExchange`]
+    #[cfg(feature = "oauth")]
+    pub fn token_exchange(mut self, token_exchange: impl TokenExchange) -> Self {
+        self.token_exchange = Arc::new(token_exchange);
+        self
+    }
+
+    /// Save the tokens obtained by the client in this store and load them in
+    /// [`DiscordIpcClient::authenticate_with_stored_token`]
+    #[cfg(feature = "oauth")]
+    pub fn token_store(mut self, token_store: impl TokenStore) -> Self {
+        self.token_store = Some(Arc::new(token_store));
+        self
+    }
+
+    /// How long before the access token expires to refresh it, which is 5 minutes by default
+    #[cfg(feature = "oauth")]
+    pub fn refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    /// Returns the connected client and the active Discord user
+    pub async fn connect(self) -> Result<(DiscordIpcClient, User)> {
+        DiscordIpcClient::connect(self).await
+    }
+}
+
+#[cfg(feature = "async")]
+impl fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("client_id", &self.client_id)
+            .field("options", &self.options)
+            .field("reconnect", &self.reconnect)
+            .field("request_timeout", &self.request_timeout)
+            .field("close_timeout", &self.close_timeout)
+            .field("clear_activity_on_close", &self.clear_activity_on_close)
+            .field("check_scopes", &self.check_scopes)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,121 @@
+use crate::builder::ConnectOptions;
+use crate::utils::get_os_pipe_paths;
+#[cfg(feature = "async")]
+use crate::{
+    Result,
+    ipc::handshake,
+    ipc_socket::connect,
+    models::receive::events::ReadyData,
+    transport::{DefaultTransport, Transport},
+};
+
+use std::path::{Path, PathBuf};
+
+/// Where a discovered endpoint was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointSource {
+    /// The temporary directory, `/tmp` on Linux and `$TMPDIR` on macOS
+    Tmp,
+    /// The runtime directory of Discord installed via Flatpak
+    Flatpak,
+    /// The runtime directory of Discord installed via Snap
+    Snap,
+    /// `$XDG_RUNTIME_DIR`
+    XdgRuntime,
+    /// A Windows named pipe
+    NamedPipe,
+}
+
+/// An IPC socket (named pipe on Windows) of a running Discord client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    /// The path of the socket
+    pub path: PathBuf,
+    /// The `N` in `discord-ipc-N`, which tells apart simultaneously running clients such as Stable and Canary
+    pub index: u8,
+    /// Where the socket was found
+    pub source: EndpointSource,
+}
+
+impl Endpoint {
+    /// Connect options that select exactly this endpoint
+    pub fn connect_options(&self) -> ConnectOptions {
+        ConnectOptions {
+            pipe_path: Some(self.path.clone()),
+            ..ConnectOptions::default()
+        }
+    }
+
+    /// Connect and perform a handshake, returning the READY payload with the connected user and the server's
+    /// environment. The connection is closed afterwards
+    ///
+    /// This uses the [`DefaultTransport`], use [`Endpoint::probe_with`] to select another one
+    #[cfg(feature = "async")]
+    pub async fn probe(&self, client_id: &str) -> Result<ReadyData> {
+        self.probe_with(client_id, &DefaultTransport::default())
+            .await
+    }
+
+    /// Like [`Endpoint::probe`], but connects with the given transport
+    #[cfg(feature = "async")]
+    pub async fn probe_with(
+        &self,
+        client_id: &str,
+        transport: &dyn Transport,
+    ) -> Result<ReadyData> {
+        let options = self.connect_options();
+        let (mut reader, writer) = connect(&options, transport).await?;
+        handshake(
+            client_id,
+            &mut reader,
+            &writer,
+            transport,
+            options.handshake_timeout,
+        )
+        .await
+    }
+}
+
+/// Finds every endpoint in the platform's default locations, in the order the client searches them
+///
+/// Unlike connecting, this ignores the `DISCORD_IPC_PATH` environment variable. Socket files left behind by a
+/// Discord client that exited uncleanly are also returned, use [`Endpoint::probe`] to tell which are live
+pub fn discover_endpoints() -> Vec<Endpoint> {
+    let prefixes = get_os_pipe_paths();
+    let mut endpoints = Vec::new();
+
+    for index in 0..10 {
+        for (prefix, source) in &prefixes {
+            let path = format!("{}{}", prefix, index);
+            if Path::new(&path).exists() {
+                endpoints.push(Endpoint {
+                    path: PathBuf::from(path),
+                    index,
+                    source: *source,
+                });
+            }
+        }
+    }
+
+    endpoints
+}
+
+/// Discovers every endpoint and probes each of them in turn with the [`DefaultTransport`]
+#[cfg(feature = "async")]
+pub async fn probe_endpoints(client_id: &str) -> Vec<(Endpoint, Result<ReadyData>)> {
+    probe_endpoints_with(client_id, &DefaultTransport::default()).await
+}
+
+/// Like [`probe_endpoints`], but connects with the given transport
+#[cfg(feature = "async")]
+pub async fn probe_endpoints_with(
+    client_id: &str,
+    transport: &dyn Transport,
+) -> Vec<(Endpoint, Result<ReadyData>)> {
+    let mut probed = Vec::new();
+    for endpoint in discover_endpoints() {
+        let ready = endpoint.probe_with(client_id, transport).await;
+        probed.push((endpoint, ready));
+    }
+    probed
+}
@@ -0,0 +1,47 @@
+use crate::models::receive::ReceivedItem;
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::sync::broadcast;
+use tokio_stream::{
+    Stream,
+    wrappers::{BroadcastStream, errors::BroadcastStreamRecvError},
+};
+
+/// Number of values buffered for each [`EventStream`] before the oldest are dropped
+pub(crate) const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// A stream of every value received from the RPC server that is not a response to a pending request
+///
+/// Each stream receives its own copy of every value. A stream that falls more than 256 values behind
+/// skips the oldest ones and yields [`ReceivedItem::Lagged`] with the number of skipped values in their place
+pub struct EventStream {
+    inner: BroadcastStream<ReceivedItem>,
+}
+
+impl EventStream {
+    pub(crate) fn new(receiver: broadcast::Receiver<ReceivedItem>) -> Self {
+        Self {
+            inner: BroadcastStream::new(receiver),
+        }
+    }
+
+    /// Wait for the next value, returning None once the client has been dropped
+    pub async fn recv(&mut self) -> Option<ReceivedItem> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl Stream for EventStream {
+    type Item = ReceivedItem;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ReceivedItem>> {
+        Pin::new(&mut self.inner).poll_next(cx).map(|item| {
+            item.map(|item| match item {
+                Ok(item) => item,
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => ReceivedItem::Lagged(skipped),
+            })
+        })
+    }
+}
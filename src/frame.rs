@@ -0,0 +1,251 @@
+use crate::models::receive::{ReceivedItem, commands::ReturnedCommand, events::ReturnedEvent};
+use crate::{DiscordRPCError, Result};
+
+use std::borrow::Cow;
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{
+        self, DeserializeOwned, DeserializeSeed, MapAccess,
+        value::{CowStrDeserializer, MapAccessDeserializer, StrDeserializer},
+    },
+};
+use serde_json::value::RawValue;
+
+/// Size of the header preceding every frame, made up of the opcode and the payload length
+pub const HEADER_SIZE: usize = 8;
+
+/// The largest payload accepted by a [`FrameCodec`] unless configured otherwise
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// The opcode of a frame sent over the IPC socket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum OpCode {
+    /// The first frame sent by the client, identifying the application
+    Handshake = 0,
+    /// A command, command response or event
+    Frame = 1,
+    /// The connection is being closed, carrying a close code and message
+    Close = 2,
+    /// A keepalive request, which must be answered with a pong carrying the same payload
+    Ping = 3,
+    /// The answer to a ping
+    Pong = 4,
+}
+
+impl TryFrom<u32> for OpCode {
+    type Error = DiscordRPCError;
+
+    fn try_from(value: u32) -> Result<Self> {
+        match value {
+            0 => Ok(Self::Handshake),
+            1 => Ok(Self::Frame),
+            2 => Ok(Self::Close),
+            3 => Ok(Self::Ping),
+            4 => Ok(Self::Pong),
+            _ => Err(DiscordRPCError::UnknownOpCode(value)),
+        }
+    }
+}
+
+/// A frame received over the IPC socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub opcode: OpCode,
+    /// The JSON payload as sent by the peer
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    /// Deserializes the payload straight from its bytes
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.payload)?)
+    }
+
+    /// Deserializes the payload into a [`ReceivedItem`] along with its nonce, reading the payload only once
+    ///
+    /// The nonce is returned even if the item cannot be deserialized, so that the error can be delivered to the
+    /// request it belongs to
+    pub fn parse_item(&self) -> (Option<String>, Result<ReceivedItem>) {
+        let envelope = match serde_json::from_slice::<Envelope>(&self.payload) {
+            Ok(envelope) => envelope,
+            Err(error) => return (None, Err(error.into())),
+        };
+
+        // Like the untagged ReceivedItem, try the payload as an event before trying it as a command response
+        let event = envelope.evt.map(|evt| {
+            ReturnedEvent::deserialize(MapAccessDeserializer::new(Adjacent::new(
+                ("evt", evt),
+                envelope.data,
+            )))
+        });
+        let item = match (event, envelope.cmd) {
+            (Some(Ok(event)), _) => Ok(ReceivedItem::Event(Box::new(event))),
+            (event, Some(cmd)) if cmd != "DISPATCH" => ReturnedCommand::deserialize(
+                MapAccessDeserializer::new(Adjacent::new(("cmd", cmd), envelope.data)),
+            )
+            .map(|command| ReceivedItem::Command(Box::new(command)))
+            .map_err(|error| match event {
+                Some(Err(event_error)) => event_error,
+                _ => error,
+            }),
+            (Some(Err(error)), _) => Err(error),
+            (None, _) => Err(de::Error::custom(
+                "the payload is neither an event nor a command response",
+            )),
+        };
+        (envelope.nonce, item.map_err(DiscordRPCError::from))
+    }
+}
+
+/// The fields of a payload that decide where it is routed, leaving the data unparsed
+#[derive(Deserialize)]
+struct Envelope<'a> {
+    #[serde(borrow, default)]
+    cmd: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    evt: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "raw_value")]
+    data: Option<&'a RawValue>,
+    #[serde(default)]
+    nonce: Option<String>,
+}
+
+/// Keeps the data as it is, including null, which Option would otherwise turn into None
+fn raw_value<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<&'de RawValue>, D::Error> {
+    <&RawValue>::deserialize(deserializer).map(Some)
+}
+
+/// Presents a tag and its content to an adjacently tagged enum as a map with the tag first, so that serde
+/// deserializes the content straight into the variant instead of buffering it
+struct Adjacent<'a> {
+    tag: Option<(&'static str, Cow<'a, str>)>,
+    data: Option<&'a RawValue>,
+}
+
+impl<'a> Adjacent<'a> {
+    fn new(tag: (&'static str, Cow<'a, str>), data: Option<&'a RawValue>) -> Self {
+        Self {
+            tag: Some(tag),
+            data,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for Adjacent<'de> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> serde_json::Result<Option<K::Value>> {
+        let key = match (&self.tag, self.data) {
+            (Some((key, _)), _) => *key,
+            (None, Some(_)) => "data",
+            (None, None) => return Ok(None),
+        };
+        seed.deserialize(StrDeserializer::new(key)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> serde_json::Result<V::Value> {
+        if let Some((_, tag)) = self.tag.take() {
+            return seed.deserialize(CowStrDeserializer::new(tag));
+        }
+        match self.data.take() {
+            Some(data) => seed.deserialize(data),
+            None => Err(de::Error::custom("no value left in the payload")),
+        }
+    }
+}
+
+/// Encodes frames into and decodes frames from bytes, refusing payloads larger than the maximum frame size
+///
+/// The length in a frame header is checked before any memory is allocated for the payload, so a malformed
+/// header fails with [`DiscordRPCError::FrameTooLarge`] instead of exhausting memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameCodec {
+    max_frame_size: usize,
+}
+
+impl Default for FrameCodec {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_FRAME_SIZE)
+    }
+}
+
+impl FrameCodec {
+    pub fn new(max_frame_size: usize) -> Self {
+        Self { max_frame_size }
+    }
+
+    /// The largest payload this codec accepts, in bytes
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    /// Encodes the header for a payload of the given length
+    pub fn encode_header(&self, opcode: OpCode, length: usize) -> Result<[u8; HEADER_SIZE]> {
+        self.check_length(length)?;
+        let mut header = [0u8; HEADER_SIZE];
+        header[..4].copy_from_slice(&(opcode as u32).to_le_bytes());
+        header[4..].copy_from_slice(&(length as u32).to_le_bytes());
+        Ok(header)
+    }
+
+    /// Appends a frame to the buffer
+    pub fn encode(&self, opcode: OpCode, payload: &[u8], dst: &mut Vec<u8>) -> Result<()> {
+        let header = self.encode_header(opcode, payload.len())?;
+        dst.reserve(HEADER_SIZE + payload.len());
+        dst.extend_from_slice(&header);
+        dst.extend_from_slice(payload);
+        Ok(())
+    }
+
+    /// Decodes a header into the raw opcode and the payload length
+    pub fn decode_header(&self, header: [u8; HEADER_SIZE]) -> Result<(u32, usize)> {
+        let opcode = u32::from_le_bytes(header[..4].try_into()?);
+        let length = u32::from_le_bytes(header[4..].try_into()?) as usize;
+        self.check_length(length)?;
+        Ok((opcode, length))
+    }
+
+    /// Removes the next complete frame from the front of the buffer, returning None if more bytes are needed
+    /// A frame with an unknown opcode is removed before [`DiscordRPCError::UnknownOpCode`] is returned
+    ///
+    /// The payload takes over the buffer's allocation instead of being copied into a new one, and only the bytes
+    /// of any frames that follow it are moved into a fresh buffer
+    pub fn decode(&self, src: &mut Vec<u8>) -> Result<Option<Frame>> {
+        let Some(header) = src.first_chunk::<HEADER_SIZE>() else {
+            return Ok(None);
+        };
+        let (opcode, length) = self.decode_header(*header)?;
+        if src.len() < HEADER_SIZE + length {
+            src.reserve(HEADER_SIZE + length - src.len());
+            return Ok(None);
+        }
+
+        let rest = src.split_off(HEADER_SIZE + length);
+        let mut payload = std::mem::replace(src, rest);
+        payload.drain(..HEADER_SIZE);
+        Ok(Some(Frame {
+            opcode: OpCode::try_from(opcode)?,
+            payload,
+        }))
+    }
+
+    fn check_length(&self, length: usize) -> Result<()> {
+        if length > self.max_frame_size || length > u32::MAX as usize {
+            return Err(DiscordRPCError::FrameTooLarge {
+                length,
+                max: self.max_frame_size,
+            });
+        }
+        Ok(())
+    }
+}
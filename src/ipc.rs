@@ -1,109 +1,752 @@
-use crate::ipc_socket::DiscordIpcSocket;
-use crate::models::receive::{ReceivedItem, events::ReturnedEvent};
-use crate::models::send::commands::{AuthenticateArgs, SentCommand};
-use crate::models::shared::User;
-use crate::utils::create_packet_json;
+use crate::builder::{ClientBuilder, ConnectOptions};
+use crate::event_stream::{EVENT_CHANNEL_CAPACITY, EventStream};
+use crate::frame::{Frame, OpCode};
+use crate::ipc_socket::{DiscordIpcReader, DiscordIpcWriter, connect};
+use crate::models::receive::{
+    ReceivedItem,
+    commands::{AuthenticateData, ReturnedCommand, SetActivityData},
+    events::{CloseData, ReadyData, ReturnedEvent},
+};
+use crate::models::send::{
+    commands::{AuthenticateArgs, AuthorizeArgs, SentCommand, SetActivityArgs},
+    events::SubscribeableEvent,
+};
+use crate::models::shared::{Scope, User, activity::Activity};
+#[cfg(feature = "oauth")]
+use crate::oauth::OAuth;
+use crate::reconnect::ReconnectConfig;
+use crate::subscription::{EventPayload, Subscription};
+use crate::timeout::{TimeoutPhase, with_timeout};
+use crate::transport::{Transport, race};
+use crate::utils::{check_scopes, create_packet_json};
 use crate::{DiscordRPCError, Result};
 
-use serde_json::json;
-use tokio::task::JoinHandle;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-#[allow(dead_code)]
-enum OpCodes {
-    Handshake,
-    Frame,
-    Close,
-    Ping,
-    Pong,
+use serde_json::{Value, json};
+use tokio::sync::{broadcast, oneshot, watch};
+
+type EventHandler = Arc<dyn Fn(ReceivedItem) + Send + Sync>;
+
+/// The close code sent by [`DiscordIpcClient::close`], which is the WebSocket code for a normal closure
+const CLOSE_CODE: u32 = 1000;
+const CLOSE_REASON: &str = "The client closed the connection";
+
+/// State shared between the client and its background tasks
+pub(crate) struct Shared {
+    pub(crate) client_id: String,
+    options: ConnectOptions,
+    pub(crate) transport: Arc<dyn Transport>,
+    writer: DiscordIpcWriter,
+    /// Requests awaiting a response from the RPC server keyed by nonce, or None while disconnected
+    pending: Mutex<Option<HashMap<String, oneshot::Sender<Result<ReceivedItem>>>>>,
+    /// The user-provided handler for values that are not responses to a pending request
+    handler: Mutex<Option<EventHandler>>,
+    /// Broadcasts values that are not responses to a pending request to every [`EventStream`]
+    events: broadcast::Sender<ReceivedItem>,
+    /// How long to wait for the response to each command, or None to wait indefinitely
+    request_timeout: Option<Duration>,
+    /// How to reconnect after the connection is lost, or None to stay disconnected
+    reconnect: Mutex<Option<ReconnectConfig>>,
+    /// State that is restored after reconnecting
+    session: Mutex<Session>,
+    /// The live [`Subscription`] handles for each event
+    subscriptions: Mutex<HashMap<SubscribeableEvent, SubscriptionEntry>>,
+    /// How long [`DiscordIpcClient::close`] waits for the activity to be cleared and for the RPC server to close the connection
+    close_timeout: Option<Duration>,
+    clear_activity_on_close: bool,
+    /// Whether to reject commands that need scopes which were not granted
+    check_scopes: bool,
+    /// Set once the client stops, which stops the background tasks
+    stop: watch::Sender<bool>,
+    /// Completes once the reader task has stopped
+    stopped: Mutex<Option<oneshot::Receiver<()>>>,
+    #[cfg(feature = "oauth")]
+    pub(crate) oauth: OAuth,
+}
+
+/// The parts of the connection state that were set up by commands
+#[derive(Default)]
+struct Session {
+    access_token: Option<String>,
+    subscriptions: HashSet<SubscribeableEvent>,
+    activity: Option<Activity>,
+    /// The scopes granted by the last successful AUTHENTICATE
+    scopes: Option<Vec<Scope>>,
 }
 
+/// The live handles for an event and whether it is subscribed on the RPC server
+struct SubscriptionEntry {
+    handles: usize,
+    /// Held while SUBSCRIBE or UNSUBSCRIBE is in flight, so that they reach the RPC server in the order they are sent
+    subscribed: Arc<tokio::sync::Mutex<bool>>,
+}
+
+/// A handle to a connection to the RPC server
+///
+/// The client is cheap to clone, and every clone sends its commands over the same connection, so it can be
+/// shared between tasks without a lock. The connection is dropped once the last clone is dropped
+#[derive(Clone)]
 pub struct DiscordIpcClient {
     pub client_id: String,
-    socket: DiscordIpcSocket,
-    event_task: Option<JoinHandle<()>>,
+    pub(crate) shared: Arc<Shared>,
+    _shutdown: Arc<Shutdown>,
+}
+
+/// Stops the background tasks, closing the connection, and fails pending requests once the last clone of the
+/// client is dropped
+struct Shutdown {
+    shared: Arc<Shared>,
 }
 
 impl DiscordIpcClient {
     /// Returns a newly constructed client and the active Discord user
     pub async fn create(client_id: String) -> Result<(DiscordIpcClient, User)> {
-        let socket = DiscordIpcSocket::new().await?;
-        let mut client = Self {
+        Self::builder(client_id).connect().await
+    }
+
+    /// Returns a builder to configure how the client connects
+    pub fn builder(client_id: impl Into<String>) -> ClientBuilder {
+        ClientBuilder::new(client_id)
+    }
+
+    pub(crate) async fn connect(builder: ClientBuilder) -> Result<(DiscordIpcClient, User)> {
+        let ClientBuilder {
             client_id,
-            socket,
-            event_task: None,
+            options,
+            reconnect,
+            request_timeout,
+            close_timeout,
+            clear_activity_on_close,
+            check_scopes,
+            transport,
+            #[cfg(feature = "oauth")]
+            token_exchange,
+            #[cfg(feature = "oauth")]
+            token_store,
+            #[cfg(feature = "oauth")]
+            refresh_margin,
+        } = builder;
+        let (mut reader, writer) = connect(&options, &*transport).await?;
+        let user = handshake(
+            &client_id,
+            &mut reader,
+            &writer,
+            &*transport,
+            options.handshake_timeout,
+        )
+        .await?
+        .user;
+
+        let (finished, stopped) = oneshot::channel();
+        let shared = Arc::new(Shared {
+            client_id: client_id.clone(),
+            options,
+            transport,
+            writer,
+            pending: Mutex::new(Some(HashMap::new())),
+            handler: Mutex::new(None),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            request_timeout,
+            reconnect: Mutex::new(reconnect),
+            session: Mutex::default(),
+            subscriptions: Mutex::default(),
+            close_timeout,
+            clear_activity_on_close,
+            check_scopes,
+            stop: watch::Sender::new(false),
+            stopped: Mutex::new(Some(stopped)),
+            #[cfg(feature = "oauth")]
+            oauth: OAuth::new(token_exchange, token_store, refresh_margin),
+        });
+        let reading = shared.clone();
+        let stopping = shared.stopping();
+        shared.transport.spawn(Box::pin(async move {
+            // The read loop emits SocketClosed itself when the connection closes, but not when it is stopped
+            let closed = async {
+                read_loop(reader, reading.clone()).await;
+                false
+            };
+            let stopped = async {
+                stopping.await;
+                true
+            };
+            if race(closed, stopped).await {
+                reading.emit(ReceivedItem::SocketClosed);
+            }
+            let _ = finished.send(());
+        }));
+
+        let client = Self {
+            client_id,
+            shared: shared.clone(),
+            _shutdown: Arc::new(Shutdown { shared }),
         };
+        Ok((client, user))
+    }
 
-        client
-            .socket
-            .send(
-                &json!({ "v": 1, "client_id": client.client_id }).to_string(),
-                OpCodes::Handshake as u8,
-            )
-            .await?;
-        let (_opcode, payload) = client.socket.recv().await?;
-        let payload = serde_json::from_str(&payload)?;
+    /// Automatically reconnect whenever the connection to the RPC server is lost
+    ///
+    /// After reconnecting, the client authenticates again with the last access token, re-sends every active
+    /// subscription and re-applies the last activity. Progress is reported to the event handler through
+    /// [`ReceivedItem::Reconnecting`] and [`ReceivedItem::Reconnected`], and [`ReceivedItem::SocketClosed`]
+    /// is only emitted once the client gives up. The client does not reconnect after the RPC server closes
+    /// the connection with a CLOSE frame
+    pub fn enable_reconnect(&self, config: ReconnectConfig) {
+        *self.shared.reconnect.lock().unwrap() = Some(config);
+    }
 
-        match payload {
-            ReturnedEvent::Ready(data) => Ok((client, data.user)),
-            _ => Err(DiscordRPCError::CouldNotConnect),
+    /// Stop reconnecting when the connection to the RPC server is lost
+    pub fn disable_reconnect(&self) {
+        self.shared.reconnect.lock().unwrap().take();
+    }
+
+    /// Request authorization from the user, returning the OAuth2 authorization code once they accept
+    pub async fn authorize(&self, args: AuthorizeArgs) -> Result<String> {
+        match self.request(SentCommand::Authorize(args)).await? {
+            ReturnedCommand::Authorize { code } => Ok(code),
+            _ => Err(DiscordRPCError::UnexpectedResponse),
         }
     }
 
     /// Authenticate with the RPC server using an OAuth2 access token
-    /// This method will hang if called after setup_event_handler
-    pub async fn authenticate(&mut self, access_token: String) -> Result<()> {
+    pub async fn authenticate(&self, access_token: String) -> Result<AuthenticateData> {
         let command = SentCommand::Authenticate(AuthenticateArgs { access_token });
-        self.emit_command(&command).await?;
-        self.socket.recv().await?;
+        match self.request(command).await? {
+            ReturnedCommand::Authenticate(data) => Ok(data),
+            _ => Err(DiscordRPCError::UnexpectedResponse),
+        }
+    }
+
+    /// Set the Rich Presence activity of the user, returning the activity as displayed by Discord
+    pub async fn set_activity(&self, activity: Activity) -> Result<Option<SetActivityData>> {
+        self.send_activity(Some(activity)).await
+    }
+
+    /// Clear the Rich Presence activity of the user
+    pub async fn clear_activity(&self) -> Result<()> {
+        self.send_activity(None).await?;
         Ok(())
     }
 
+    async fn send_activity(&self, activity: Option<Activity>) -> Result<Option<SetActivityData>> {
+        let command = SentCommand::SetActivity(Box::new(SetActivityArgs {
+            pid: std::process::id(),
+            activity,
+        }));
+        match self.request(command).await? {
+            ReturnedCommand::SetActivity(data) => Ok(data.map(|data| *data)),
+            _ => Err(DiscordRPCError::UnexpectedResponse),
+        }
+    }
+
     /// Send an arbitrary JSON string payload to the RPC server
-    pub async fn emit_string(&mut self, payload: &str) -> Result<()> {
-        self.socket.send(payload, OpCodes::Frame as u8).await
+    pub async fn emit_string(&self, payload: &str) -> Result<()> {
+        self.shared
+            .writer
+            .send(payload.as_bytes().to_vec(), OpCode::Frame)
+            .await
     }
 
     /// Send a command to the RPC server
-    pub async fn emit_command(&mut self, command: &SentCommand) -> Result<()> {
+    /// Commands that need scopes which were not granted fail with [`DiscordRPCError::MissingScope`]
+    pub async fn emit_command(&self, command: &SentCommand) -> Result<()> {
         let mut command_json = command.to_json()?;
-        let json_string = create_packet_json(&mut command_json)?;
-        self.emit_string(&json_string).await
+        self.shared.check_scopes(command, &command_json)?;
+        let (_nonce, json_string) = create_packet_json(&mut command_json)?;
+        self.emit_string(&json_string).await?;
+        self.shared.remember(command);
+        Ok(())
+    }
+
+    /// Send a command to the RPC server and wait for the response carrying the same nonce
+    /// Error responses are returned as [`DiscordRPCError::Rpc`], and commands that need scopes which were not
+    /// granted fail with [`DiscordRPCError::MissingScope`] before they are sent
+    pub async fn request(&self, command: SentCommand) -> Result<ReturnedCommand> {
+        self.shared.request(command).await
+    }
+
+    /// The OAuth2 scopes granted by the last successful AUTHENTICATE, or None before authenticating
+    pub fn granted_scopes(&self) -> Option<Vec<Scope>> {
+        self.shared.session.lock().unwrap().scopes.clone()
     }
 
     /// Set up an event handler that will be called whenever a value is received from the RPC server
-    pub async fn setup_event_handler<F>(&mut self, func: F)
+    /// Responses to commands sent with [`DiscordIpcClient::request`] are not passed to the handler.
+    /// The handler is called from the client's reader task, which runs regardless of whether a handler is set
+    pub fn setup_event_handler<F>(&self, func: F)
     where
         F: Fn(ReceivedItem) + Send + Sync + 'static,
     {
-        if let Some(handle) = self.event_task.take() {
-            handle.abort();
+        *self.shared.handler.lock().unwrap() = Some(Arc::new(func));
+    }
+
+    /// Remove the event handler
+    pub fn remove_event_handler(&self) {
+        self.shared.handler.lock().unwrap().take();
+    }
+
+    /// Returns a stream of every value received from the RPC server from now on, as an alternative to the event handler
+    /// Any number of streams can be consumed independently, and responses to commands sent with
+    /// [`DiscordIpcClient::request`] are not included
+    pub fn events(&self) -> EventStream {
+        EventStream::new(self.shared.events.subscribe())
+    }
+
+    /// Subscribe to an event, returning a handle that yields its payloads and unsubscribes once dropped
+    ///
+    /// The payload type selects which data is extracted from the event, such as
+    /// [`SpeakingData`](crate::models::receive::events::SpeakingData) for
+    /// [`SubscribeableEvent::SpeakingStart`], or [`ReturnedEvent`] to receive the events as they are.
+    /// Only the first handle for an event sends a SUBSCRIBE command. Events whose payloads do not tell apart
+    /// their arguments, see [`SubscribeableEvent::payload_identifies_arguments`], fail with
+    /// [`DiscordRPCError::SubscriptionConflict`] while a handle for the same event with different arguments is live
+    pub async fn subscribe<T: EventPayload>(
+        &self,
+        event: SubscribeableEvent,
+    ) -> Result<Subscription<T>> {
+        let events = self.events();
+        let entry = self.shared.acquire_subscription(&event)?;
+        let mut subscribed = entry.lock().await;
+        if !*subscribed {
+            if let Err(error) = self.request(SentCommand::Subscribe(event.clone())).await {
+                self.shared.forget_subscription(&event, &entry, true);
+                return Err(error);
+            }
+            *subscribed = true;
         }
+        drop(subscribed);
+        Ok(Subscription::new(event, events, self.shared.clone()))
+    }
 
-        let mut socket_clone = self.socket.clone();
-        self.event_task = Some(tokio::spawn(async move {
-            loop {
-                let Ok((_opcode, payload)) = socket_clone.recv().await else {
-                    func(ReceivedItem::SocketClosed);
-                    break;
-                };
-                match serde_json::from_str::<ReceivedItem>(&payload) {
-                    Ok(item) => func(item),
-                    Err(error) => eprintln!("Failed to deserialize payload {}: {}", payload, error),
+    /// Close the connection, first clearing the activity if enabled with [`ClientBuilder::clear_activity_on_close`]
+    ///
+    /// Clearing the activity waits for the response for at most the close timeout. Then this sends a CLOSE frame
+    /// and waits for the RPC server to close the connection, for at most the close timeout, before stopping the
+    /// background tasks. This closes the connection of every clone of the client, whose commands fail with
+    /// [`DiscordRPCError::ConnectionClosed`] from then on. Returns an error if the activity could not be cleared
+    /// or the CLOSE frame could not be sent, in which case the connection is closed all the same
+    pub async fn close(self) -> Result<()> {
+        let shared = &self.shared;
+        shared.reconnect.lock().unwrap().take();
+
+        let has_activity = shared.session.lock().unwrap().activity.is_some();
+        let cleared = if shared.clear_activity_on_close && has_activity {
+            with_timeout(
+                &*shared.transport,
+                shared.close_timeout,
+                TimeoutPhase::Request,
+                self.clear_activity(),
+            )
+            .await
+        } else {
+            Ok(())
+        };
+
+        let payload = json!({ "code": CLOSE_CODE, "message": CLOSE_REASON }).to_string();
+        let sent = shared
+            .writer
+            .send(payload.into_bytes(), OpCode::Close)
+            .await;
+
+        let stopped = shared.stopped.lock().unwrap().take();
+        if let Some(mut stopped) = stopped {
+            let finished = match (&sent, shared.close_timeout) {
+                (Err(_), _) => false,
+                (Ok(()), Some(timeout)) => {
+                    let acknowledged = async {
+                        let _ = (&mut stopped).await;
+                        true
+                    };
+                    let elapsed = async {
+                        shared.transport.sleep(timeout).await;
+                        false
+                    };
+                    race(acknowledged, elapsed).await
+                }
+                (Ok(()), None) => {
+                    let _ = (&mut stopped).await;
+                    true
+                }
+            };
+            shared.stop.send_replace(true);
+            if !finished {
+                let _ = stopped.await;
+            }
+        }
+
+        shared.writer.close().await;
+        shared.pending.lock().unwrap().take();
+        cleared.and(sent)
+    }
+}
+
+impl Drop for Shutdown {
+    fn drop(&mut self) {
+        self.shared.stop.send_replace(true);
+        self.shared.pending.lock().unwrap().take();
+        self.shared.writer.abort();
+    }
+}
+
+impl Shared {
+    /// Completes once the client stops
+    pub(crate) fn stopping(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut stop = self.stop.subscribe();
+        async move {
+            let _ = stop.wait_for(|stopped| *stopped).await;
+        }
+    }
+
+    pub(crate) async fn request(&self, command: SentCommand) -> Result<ReturnedCommand> {
+        let mut command_json = command.to_json()?;
+        self.check_scopes(&command, &command_json)?;
+        let (nonce, json_string) = create_packet_json(&mut command_json)?;
+
+        let (sender, receiver) = oneshot::channel();
+        match self.pending.lock().unwrap().as_mut() {
+            Some(pending) => pending.insert(nonce.clone(), sender),
+            None => return Err(DiscordRPCError::ConnectionClosed),
+        };
+
+        // The timeout covers sending too, which waits while the RPC server is not reading
+        let response = with_timeout(
+            &*self.transport,
+            self.request_timeout,
+            TimeoutPhase::Request,
+            async {
+                self.writer
+                    .send(json_string.into_bytes(), OpCode::Frame)
+                    .await?;
+                receiver
+                    .await
+                    .map_err(|_| DiscordRPCError::ConnectionClosed)?
+            },
+        )
+        .await;
+        if response.is_err()
+            && let Some(pending) = self.pending.lock().unwrap().as_mut()
+        {
+            pending.remove(&nonce);
+        }
+
+        let response = match response? {
+            ReceivedItem::Command(command) => *command,
+            ReceivedItem::Event(event) => match *event {
+                ReturnedEvent::Error(error) => {
+                    return Err(DiscordRPCError::Rpc {
+                        code: error.code,
+                        message: error.message,
+                    });
                 }
+                _ => return Err(DiscordRPCError::UnexpectedResponse),
+            },
+            _ => return Err(DiscordRPCError::UnexpectedResponse),
+        };
+
+        self.remember(&command);
+        Ok(response)
+    }
+
+    /// Counts a new handle for the event, returning the lock that orders its SUBSCRIBE and UNSUBSCRIBE commands
+    fn acquire_subscription(
+        &self,
+        event: &SubscribeableEvent,
+    ) -> Result<Arc<tokio::sync::Mutex<bool>>> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        if !event.payload_identifies_arguments()
+            && subscriptions.iter().any(|(other, entry)| {
+                entry.handles > 0 && other != event && other.name() == event.name()
+            })
+        {
+            return Err(DiscordRPCError::SubscriptionConflict(
+                event.name().to_owned(),
+            ));
+        }
+        let entry = subscriptions
+            .entry(event.clone())
+            .or_insert_with(|| SubscriptionEntry {
+                handles: 0,
+                subscribed: Arc::default(),
+            });
+        entry.handles += 1;
+        Ok(entry.subscribed.clone())
+    }
+
+    /// Stops counting a handle if `release` is set, then removes the entry for the event once no handles remain
+    fn forget_subscription(
+        &self,
+        event: &SubscribeableEvent,
+        subscribed: &Arc<tokio::sync::Mutex<bool>>,
+        release: bool,
+    ) {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        let Some(entry) = subscriptions.get_mut(event) else {
+            return;
+        };
+        if !Arc::ptr_eq(&entry.subscribed, subscribed) {
+            return;
+        }
+        if release {
+            entry.handles -= 1;
+        }
+        if entry.handles == 0 {
+            subscriptions.remove(event);
+        }
+    }
+
+    /// Drops a subscription handle, unsubscribing from the event once no handles remain
+    pub(crate) fn release_subscription(self: &Arc<Self>, event: &SubscribeableEvent) {
+        if *self.stop.borrow() {
+            return;
+        }
+        let subscribed = {
+            let mut subscriptions = self.subscriptions.lock().unwrap();
+            let Some(entry) = subscriptions.get_mut(event) else {
+                return;
+            };
+            entry.handles -= 1;
+            if entry.handles > 0 {
+                return;
             }
+            entry.subscribed.clone()
+        };
+
+        // Outside of the runtime, the event stays subscribed on the RPC server and is reused by the next handle
+        let shared = self.clone();
+        let event = event.clone();
+        self.transport.try_spawn(Box::pin(async move {
+            // A handle for the event may have been created while waiting for the lock, in which case the
+            // subscription is kept
+            let mut guard = subscribed.lock().await;
+            let remaining = shared
+                .subscriptions
+                .lock()
+                .unwrap()
+                .get(&event)
+                .map(|entry| entry.handles);
+            if remaining != Some(0) || !*guard {
+                return;
+            }
+            let command = SentCommand::Unsubscribe(event.clone());
+            if let Err(error) = shared.request(command.clone()).await {
+                eprintln!("Failed to unsubscribe: {}", error);
+                shared.remember(&command);
+            }
+            *guard = false;
+            shared.forget_subscription(&event, &subscribed, false);
         }));
     }
 
-    /// Remove the event handler
-    pub fn remove_event_handler(&mut self) {
-        if let Some(handle) = self.event_task.take() {
-            handle.abort();
+    fn check_scopes(&self, command: &SentCommand, command_json: &Value) -> Result<()> {
+        if !self.check_scopes {
+            return Ok(());
+        }
+        let session = self.session.lock().unwrap();
+        check_scopes(command, command_json, session.scopes.as_deref())
+    }
+
+    /// Records the effect of a sent command on the state that is restored after reconnecting
+    pub(crate) fn remember(&self, command: &SentCommand) {
+        let mut session = self.session.lock().unwrap();
+        match command {
+            SentCommand::Authenticate(args) => {
+                session.access_token = Some(args.access_token.clone());
+            }
+            SentCommand::Subscribe(event) => {
+                session.subscriptions.insert(event.clone());
+            }
+            SentCommand::Unsubscribe(event) => {
+                session.subscriptions.remove(event);
+            }
+            SentCommand::SetActivity(args) => session.activity = args.activity.clone(),
+            _ => {}
+        }
+    }
+
+    fn emit(&self, item: ReceivedItem) {
+        if self.events.receiver_count() > 0 {
+            let _ = self.events.send(item.clone());
+        }
+        let func = self.handler.lock().unwrap().clone();
+        if let Some(func) = func {
+            func(item);
+        }
+    }
+
+    /// Delivers a frame to the pending request with a matching nonce, returning it if there is none
+    fn route_frame(&self, frame: &Frame) -> Option<ReceivedItem> {
+        let (nonce, item) = frame.parse_item();
+        let sender = nonce.and_then(|nonce| self.pending.lock().unwrap().as_mut()?.remove(&nonce));
+        if let Ok(ReceivedItem::Command(command)) = &item
+            && let ReturnedCommand::Authenticate(data) = &**command
+        {
+            self.session.lock().unwrap().scopes = Some(data.scopes.clone());
+        }
+
+        match (sender, item) {
+            (Some(sender), item) => {
+                let _ = sender.send(item);
+                None
+            }
+            (None, Ok(item)) => Some(item),
+            (None, Err(error)) => {
+                eprintln!(
+                    "Failed to deserialize payload {}: {}",
+                    String::from_utf8_lossy(&frame.payload),
+                    error
+                );
+                None
+            }
+        }
+    }
+
+    /// Reconnects with exponential backoff, returning the new read half or None once the client gives up
+    async fn reconnect(&self) -> Option<DiscordIpcReader> {
+        let mut attempt = 1;
+        loop {
+            let config = self.reconnect.lock().unwrap().clone()?;
+            if !config.allows_attempt(attempt) {
+                return None;
+            }
+
+            self.emit(ReceivedItem::Reconnecting { attempt });
+            self.transport.sleep(config.delay_for(attempt)).await;
+
+            if let Ok(mut reader) = self.writer.reconnect(&self.options, &*self.transport).await
+                && let Ok(ready) = handshake(
+                    &self.client_id,
+                    &mut reader,
+                    &self.writer,
+                    &*self.transport,
+                    self.options.handshake_timeout,
+                )
+                .await
+            {
+                self.writer.resume().await;
+                *self.pending.lock().unwrap() = Some(HashMap::new());
+                self.emit(ReceivedItem::Reconnected(ready.user));
+                return Some(reader);
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Authenticates again, re-sends every subscription and re-applies the activity after reconnecting
+    async fn restore(&self) {
+        let (access_token, subscriptions, activity) = {
+            let session = self.session.lock().unwrap();
+            (
+                session.access_token.clone(),
+                session.subscriptions.clone(),
+                session.activity.clone(),
+            )
+        };
+
+        let mut commands = Vec::new();
+        if let Some(access_token) = access_token {
+            commands.push(SentCommand::Authenticate(AuthenticateArgs { access_token }));
+        }
+        commands.extend(subscriptions.into_iter().map(SentCommand::Subscribe));
+        if let Some(activity) = activity {
+            commands.push(SentCommand::SetActivity(Box::new(SetActivityArgs {
+                pid: std::process::id(),
+                activity: Some(activity),
+            })));
+        }
+
+        for command in commands {
+            if let Err(error) = self.request(command).await {
+                eprintln!("Failed to restore state after reconnecting: {}", error);
+            }
         }
     }
 }
 
-impl Drop for DiscordIpcClient {
-    fn drop(&mut self) {
-        self.remove_event_handler();
+/// Sends the handshake and waits for the READY event, returning the active Discord user
+pub(crate) async fn handshake(
+    client_id: &str,
+    reader: &mut DiscordIpcReader,
+    writer: &DiscordIpcWriter,
+    transport: &dyn Transport,
+    timeout: Option<Duration>,
+) -> Result<ReadyData> {
+    let payload = json!({ "v": 1, "client_id": client_id }).to_string();
+    let frame = with_timeout(transport, timeout, TimeoutPhase::Handshake, async {
+        writer.send(payload.into_bytes(), OpCode::Handshake).await?;
+        reader.recv().await
+    })
+    .await?;
+
+    if frame.opcode == OpCode::Close {
+        let data = frame.parse::<CloseData>()?;
+        return Err(DiscordRPCError::Closed {
+            code: data.code,
+            message: data.message,
+        });
+    }
+
+    match frame.parse()? {
+        ReturnedEvent::Ready(data) => Ok(data),
+        _ => Err(DiscordRPCError::CouldNotConnect),
+    }
+}
+
+/// Reads every frame from the socket, dispatching responses to pending requests and everything else to the event handler
+async fn read_loop(mut reader: DiscordIpcReader, shared: Arc<Shared>) {
+    loop {
+        let frame = match reader.recv().await {
+            Ok(frame) => frame,
+            Err(DiscordRPCError::UnknownOpCode(opcode)) => {
+                eprintln!("Ignoring frame with unknown opcode {}", opcode);
+                continue;
+            }
+            Err(error) => {
+                if !matches!(error, DiscordRPCError::Io(_)) {
+                    eprintln!("Dropping the connection: {}", error);
+                }
+                shared.pending.lock().unwrap().take();
+                let Some(new_reader) = shared.reconnect().await else {
+                    shared.emit(ReceivedItem::SocketClosed);
+                    break;
+                };
+                reader = new_reader;
+                let restoring = shared.clone();
+                shared
+                    .transport
+                    .spawn(Box::pin(async move { restoring.restore().await }));
+                continue;
+            }
+        };
+
+        match frame.opcode {
+            OpCode::Frame => {
+                if let Some(item) = shared.route_frame(&frame) {
+                    shared.emit(item);
+                }
+            }
+            OpCode::Ping => {
+                if let Err(error) = shared.writer.send(frame.payload, OpCode::Pong).await {
+                    eprintln!("Failed to answer ping: {}", error);
+                }
+            }
+            OpCode::Close => {
+                shared.pending.lock().unwrap().take();
+                match frame.parse::<CloseData>() {
+                    Ok(data) => shared.emit(ReceivedItem::Closed(data)),
+                    Err(error) => eprintln!(
+                        "Failed to deserialize close payload {}: {}",
+                        String::from_utf8_lossy(&frame.payload),
+                        error
+                    ),
+                }
+                shared.emit(ReceivedItem::SocketClosed);
+                break;
+            }
+            OpCode::Handshake | OpCode::Pong => {}
+        }
     }
 }
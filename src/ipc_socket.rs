@@ -1,111 +1,277 @@
-use crate::utils::{get_pipe_path, pack, unpack};
+use crate::builder::ConnectOptions;
+use crate::frame::{Frame, FrameCodec, HEADER_SIZE, OpCode};
+use crate::timeout::{TimeoutPhase, with_timeout};
+use crate::transport::{Connection, Transport, TransportReader, TransportWriter, race};
+use crate::utils::get_pipe_path;
 use crate::{DiscordRPCError, Result};
 
+use std::io::{self, IoSlice};
 use std::sync::Arc;
 
-use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    sync::Mutex,
-};
-
-#[cfg(target_family = "unix")]
-use tokio::net::{
-    UnixStream,
-    unix::{OwnedReadHalf, OwnedWriteHalf},
-};
-
-#[cfg(target_family = "windows")]
-use tokio::{
-    io::{ReadHalf, WriteHalf},
-    net::windows::named_pipe::{ClientOptions, NamedPipeClient},
-};
-
-#[cfg(target_family = "windows")]
-type ReadHalfType = ReadHalf<NamedPipeClient>;
-#[cfg(target_family = "windows")]
-type WriteHalfType = WriteHalf<NamedPipeClient>;
-
-#[cfg(target_family = "unix")]
-type ReadHalfType = OwnedReadHalf;
-#[cfg(target_family = "unix")]
-type WriteHalfType = OwnedWriteHalf;
+use tokio::sync::{mpsc, oneshot, watch};
 
-#[derive(Clone)]
-pub struct DiscordIpcSocket {
-    read_half: Arc<Mutex<ReadHalfType>>,
-    write_half: Arc<Mutex<WriteHalfType>>,
-}
+/// Number of frames that may wait for the writer task before senders wait for room
+const WRITE_QUEUE_CAPACITY: usize = 64;
 
-impl DiscordIpcSocket {
-    #[cfg(target_os = "windows")]
-    async fn get_inner_socket() -> Result<(ReadHalfType, WriteHalfType)> {
-        let path = match get_pipe_path() {
-            Some(p) => p,
-            None => return Result::Err(DiscordRPCError::PipeNotFound),
-        };
+/// Most frames written with a single vectored write
+const MAX_WRITE_BATCH: usize = 16;
 
-        if let Ok(client) = ClientOptions::new().open(path) {
-            let (read_half, write_half) = tokio::io::split(client);
-            return Ok((read_half, write_half));
-        }
+/// Connects to the RPC server, returning the read and write halves of the socket
+pub async fn connect(
+    options: &ConnectOptions,
+    transport: &dyn Transport,
+) -> Result<(DiscordIpcReader, DiscordIpcWriter)> {
+    let (read_half, write_half) = with_timeout(
+        transport,
+        options.connect_timeout,
+        TimeoutPhase::Connect,
+        get_inner_socket(options, transport),
+    )
+    .await?;
+    let codec = FrameCodec::new(options.max_frame_size);
 
-        Err(DiscordRPCError::CouldNotConnect)
-    }
+    let (queue, receiver) = mpsc::channel(WRITE_QUEUE_CAPACITY);
+    let (abort, mut aborted) = watch::channel(false);
+    transport.spawn(Box::pin(race(
+        write_loop(write_half, receiver),
+        async move {
+            let _ = aborted.wait_for(|aborted| *aborted).await;
+        },
+    )));
 
-    #[cfg(target_family = "unix")]
-    async fn get_inner_socket() -> Result<(ReadHalfType, WriteHalfType)> {
-        let path = match get_pipe_path() {
-            Some(p) => p,
-            None => return Result::Err(DiscordRPCError::PipeNotFound),
-        };
+    Ok((
+        DiscordIpcReader { read_half, codec },
+        DiscordIpcWriter {
+            queue,
+            codec,
+            abort: Arc::new(abort),
+        },
+    ))
+}
 
-        if let Ok(socket) = UnixStream::connect(&path).await {
-            return Ok(socket.into_split());
-        }
+async fn get_inner_socket(
+    options: &ConnectOptions,
+    transport: &dyn Transport,
+) -> Result<Connection> {
+    let path = match get_pipe_path(options) {
+        Some(p) => p,
+        None => return Result::Err(DiscordRPCError::PipeNotFound),
+    };
+
+    transport
+        .connect(&path)
+        .await
+        .map_err(|_| DiscordRPCError::CouldNotConnect)
+}
+
+/// The read half of the socket, owned by a single reader
+pub struct DiscordIpcReader {
+    read_half: Box<dyn TransportReader>,
+    codec: FrameCodec,
+}
 
-        Err(DiscordRPCError::CouldNotConnect)
+impl DiscordIpcReader {
+    pub async fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.read_half.read_exact(buf).await?;
+        Ok(())
     }
 
-    pub async fn new() -> Result<Self> {
-        let (read_half, write_half) = Self::get_inner_socket().await?;
-        Ok(Self {
-            read_half: Arc::new(Mutex::new(read_half)),
-            write_half: Arc::new(Mutex::new(write_half)),
+    /// Receives the next frame, returning [`DiscordRPCError::UnknownOpCode`] after consuming a frame with an unknown opcode
+    pub async fn recv(&mut self) -> Result<Frame> {
+        let mut header = [0u8; HEADER_SIZE];
+        self.read(&mut header).await?;
+        let (opcode, length) = self.codec.decode_header(header)?;
+
+        let mut payload = vec![0u8; length];
+        self.read(&mut payload).await?;
+
+        Ok(Frame {
+            opcode: OpCode::try_from(opcode)?,
+            payload,
         })
     }
+}
 
-    pub async fn write(&mut self, buf: &[u8]) -> Result<()> {
-        let mut socket = self.write_half.lock().await;
-        socket.write_all(buf).await?;
-        Ok(())
-    }
+/// A frame waiting to be written by the writer task
+struct QueuedFrame {
+    opcode: OpCode,
+    header: [u8; HEADER_SIZE],
+    payload: Vec<u8>,
+    /// Receives the result of writing the frame
+    written: oneshot::Sender<io::Result<()>>,
+}
 
-    pub async fn read(&mut self, buf: &mut [u8]) -> Result<()> {
-        let mut socket = self.read_half.lock().await;
-        socket.read_exact(buf).await?;
+enum WriterMessage {
+    Frame(QueuedFrame),
+    /// Write to the write half of a new connection from now on, writing nothing but the handshake until it completes
+    Replace(Box<dyn TransportWriter>),
+    /// The handshake on the new connection has completed, so every frame may be written again
+    Resume,
+    /// Stop once every frame queued before this message has been written, dropping the write half
+    Close(oneshot::Sender<()>),
+}
+
+/// A handle to the writer task, which owns the write half of the socket and writes queued frames in order
+///
+/// The handle may be cloned and shared between any number of senders
+#[derive(Clone)]
+pub struct DiscordIpcWriter {
+    queue: mpsc::Sender<WriterMessage>,
+    codec: FrameCodec,
+    abort: Arc<watch::Sender<bool>>,
+}
+
+impl DiscordIpcWriter {
+    /// Queues a frame, waiting until the writer task has written it and returning any error it encountered
+    pub async fn send(&self, payload: Vec<u8>, opcode: OpCode) -> Result<()> {
+        let header = self.codec.encode_header(opcode, payload.len())?;
+        let (written, result) = oneshot::channel();
+        let frame = QueuedFrame {
+            opcode,
+            header,
+            payload,
+            written,
+        };
+        self.queue
+            .send(WriterMessage::Frame(frame))
+            .await
+            .map_err(|_| DiscordRPCError::ConnectionClosed)?;
+        result
+            .await
+            .map_err(|_| DiscordRPCError::ConnectionClosed)??;
         Ok(())
     }
 
-    pub async fn send(&mut self, data: &str, opcode: u8) -> Result<()> {
-        let mut packet = pack(opcode.into(), data.len() as u32)?;
+    /// Connects a fresh socket, replacing the write half of this writer and returning the new read half
+    pub async fn reconnect(
+        &self,
+        options: &ConnectOptions,
+        transport: &dyn Transport,
+    ) -> Result<DiscordIpcReader> {
+        let (read_half, write_half) = with_timeout(
+            transport,
+            options.connect_timeout,
+            TimeoutPhase::Connect,
+            get_inner_socket(options, transport),
+        )
+        .await?;
+        self.queue
+            .send(WriterMessage::Replace(write_half))
+            .await
+            .map_err(|_| DiscordRPCError::ConnectionClosed)?;
+        Ok(DiscordIpcReader {
+            read_half,
+            codec: self.codec,
+        })
+    }
 
-        packet.extend(data.as_bytes());
+    /// Lets the writer task write every frame again once the handshake on a new connection has completed
+    pub async fn resume(&self) {
+        let _ = self.queue.send(WriterMessage::Resume).await;
+    }
 
-        self.write(&packet).await?;
+    /// Stops the writer task once it has written every frame queued so far, waiting until it has stopped
+    pub async fn close(&self) {
+        let (closed, stopped) = oneshot::channel();
+        if self.queue.send(WriterMessage::Close(closed)).await.is_ok() {
+            let _ = stopped.await;
+        }
+    }
 
-        Ok(())
+    /// Stops the writer task right away, dropping the write half along with every frame that was not written yet
+    pub fn abort(&self) {
+        self.abort.send_replace(true);
     }
+}
 
-    pub async fn recv(&mut self) -> Result<(u32, String)> {
-        let mut header = [0u8; 8];
-        self.read(&mut header).await?;
-        let (op, length) = unpack(header.to_vec())?;
+/// Writes queued frames until every [`DiscordIpcWriter`] is dropped or one closes the writer, batching frames that queue up while a
+/// write is in progress into a single vectored write
+///
+/// Between replacing the write half and resuming, frames other than the handshake are dropped unwritten, which their
+/// senders see as [`DiscordRPCError::ConnectionClosed`], since the RPC server rejects any frame sent before the handshake
+async fn write_loop(
+    mut write_half: Box<dyn TransportWriter>,
+    mut receiver: mpsc::Receiver<WriterMessage>,
+) {
+    let mut handshaking = false;
+    while let Some(message) = receiver.recv().await {
+        let mut batch = match message {
+            WriterMessage::Frame(frame) if handshaking && frame.opcode != OpCode::Handshake => {
+                continue;
+            }
+            WriterMessage::Frame(frame) => vec![frame],
+            WriterMessage::Replace(replacement) => {
+                write_half = replacement;
+                handshaking = true;
+                continue;
+            }
+            WriterMessage::Resume => {
+                handshaking = false;
+                continue;
+            }
+            WriterMessage::Close(closed) => {
+                drop(write_half);
+                let _ = closed.send(());
+                return;
+            }
+        };
 
-        let mut data = vec![0u8; length as usize];
-        self.read(&mut data).await?;
+        let mut next = None;
+        while batch.len() < MAX_WRITE_BATCH {
+            match receiver.try_recv() {
+                Ok(WriterMessage::Frame(frame))
+                    if handshaking && frame.opcode != OpCode::Handshake => {}
+                Ok(WriterMessage::Frame(frame)) => batch.push(frame),
+                Ok(message) => {
+                    next = Some(message);
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
 
-        let response = String::from_utf8(data)?;
+        let result = write_batch(&mut *write_half, &batch).await;
+        for frame in batch {
+            let result = match &result {
+                Ok(()) => Ok(()),
+                Err(error) => Err(io::Error::new(error.kind(), error.to_string())),
+            };
+            let _ = frame.written.send(result);
+        }
 
-        Ok((op, response))
+        match next {
+            Some(WriterMessage::Replace(replacement)) => {
+                write_half = replacement;
+                handshaking = true;
+            }
+            Some(WriterMessage::Resume) => handshaking = false,
+            Some(WriterMessage::Close(closed)) => {
+                drop(write_half);
+                let _ = closed.send(());
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn write_batch(
+    write_half: &mut dyn TransportWriter,
+    batch: &[QueuedFrame],
+) -> io::Result<()> {
+    let mut slices: Vec<IoSlice> = batch
+        .iter()
+        .flat_map(|frame| [IoSlice::new(&frame.header), IoSlice::new(&frame.payload)])
+        .collect();
+    let mut remaining = &mut slices[..];
+    IoSlice::advance_slices(&mut remaining, 0);
+
+    while !remaining.is_empty() {
+        let written = write_half.write_vectored(remaining).await?;
+        if written == 0 {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        IoSlice::advance_slices(&mut remaining, written);
     }
+    Ok(())
 }
@@ -1,10 +1,60 @@
+// Without a client, only the models and endpoint discovery are available
+#![cfg_attr(not(any(feature = "async", feature = "blocking")), allow(dead_code))]
+
+mod builder;
+mod discovery;
+#[cfg(feature = "async")]
+mod event_stream;
+mod frame;
+#[cfg(feature = "async")]
 mod ipc;
+#[cfg(feature = "async")]
 mod ipc_socket;
+#[cfg(feature = "async")]
+mod reconnect;
+#[cfg(feature = "async")]
+mod subscription;
+mod timeout;
 mod utils;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod models;
+#[cfg(feature = "oauth")]
+pub mod oauth;
+#[cfg(all(feature = "testing", target_family = "unix"))]
+pub mod testing;
+#[cfg(feature = "async")]
+pub mod transport;
 
+#[cfg(feature = "async")]
+pub use builder::ClientBuilder;
+pub use builder::{ConnectOptions, PIPE_PATH_ENV_VAR};
+pub use discovery::{Endpoint, EndpointSource, discover_endpoints};
+#[cfg(feature = "async")]
+pub use discovery::{probe_endpoints, probe_endpoints_with};
+#[cfg(feature = "async")]
+pub use event_stream::EventStream;
+pub use frame::{DEFAULT_MAX_FRAME_SIZE, Frame, FrameCodec, OpCode};
+#[cfg(feature = "async")]
 pub use ipc::DiscordIpcClient;
+#[cfg(feature = "async")]
+pub use reconnect::ReconnectConfig;
+#[cfg(feature = "async")]
+pub use subscription::{EventPayload, Subscription};
+pub use timeout::TimeoutPhase;
+
+#[cfg(all(
+    feature = "async",
+    not(any(feature = "tokio", feature = "smol", feature = "async-std"))
+))]
+compile_error!("the async client needs one of the tokio, smol or async-std features");
+
+#[cfg(all(
+    target_family = "windows",
+    any(feature = "smol", feature = "async-std")
+))]
+compile_error!("the smol and async-std features are only supported on Unix, use tokio on Windows");
 
 #[derive(thiserror::Error, Debug)]
 pub enum DiscordRPCError {
@@ -15,11 +65,51 @@ pub enum DiscordRPCError {
     #[error("Failed to convert from slice")]
     TryFromSlice(#[from] std::array::TryFromSliceError),
     #[error("An I/O error occurred")]
-    Io(#[from] tokio::io::Error),
+    Io(#[from] std::io::Error),
     #[error("Failed to convert UTF-8 bytes to String")]
     FromUtf8(#[from] std::string::FromUtf8Error),
     #[error("A serde_json error occurred")]
     SerdeJson(#[from] serde_json::Error),
+    #[error("The activity is invalid: {0}")]
+    InvalidActivity(#[from] models::send::ActivityValidationError),
+    #[error("The RPC server returned error {code}: {message}")]
+    Rpc {
+        code: models::receive::codes::RpcErrorCode,
+        message: String,
+    },
+    #[error("The RPC server sent an unexpected response")]
+    UnexpectedResponse,
+    #[error("The connection was closed before a response was received")]
+    ConnectionClosed,
+    #[error("The RPC server closed the connection with code {code}: {message}")]
+    Closed {
+        code: models::receive::codes::RpcCloseCode,
+        message: String,
+    },
+    #[error("Received a frame with an unknown opcode: {0}")]
+    UnknownOpCode(u32),
+    #[error("Received a frame of {length} bytes, which exceeds the maximum of {max} bytes")]
+    FrameTooLarge { length: usize, max: usize },
+    #[error("{command} needs the OAuth2 scopes {missing:?}, which were not granted")]
+    MissingScope {
+        command: String,
+        missing: Vec<models::shared::Scope>,
+    },
+    #[error(
+        "Already subscribed to {0} with different arguments, which its events cannot be told apart from"
+    )]
+    SubscriptionConflict(String),
+    #[error("Fell behind the RPC server and skipped {0} values")]
+    Lagged(u64),
+    #[error("The OAuth2 token exchange failed: {0}")]
+    TokenExchange(String),
+    #[error("The PKCE code verifier {0:?} is not 43 to 128 unreserved characters long")]
+    InvalidCodeVerifier(String),
+    #[error("Timed out during {phase} after {after:?}")]
+    Timeout {
+        phase: TimeoutPhase,
+        after: std::time::Duration,
+    },
 }
 
 pub type Result<T, E = DiscordRPCError> = std::result::Result<T, E>;
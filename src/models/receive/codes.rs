@@ -0,0 +1,157 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// <https://discord.com/developers/docs/topics/opcodes-and-status-codes#rpc-rpc-error-codes>
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(from = "u32", into = "u32")]
+pub enum RpcErrorCode {
+    /// An unknown error occurred
+    UnknownError,
+    /// You sent an invalid payload
+    InvalidPayload,
+    /// Invalid command name specified
+    InvalidCommand,
+    /// Invalid guild ID specified
+    InvalidGuild,
+    /// Invalid event name specified
+    InvalidEvent,
+    /// Invalid channel ID specified
+    InvalidChannel,
+    /// You lack permissions to access the given resource
+    InvalidPermissions,
+    /// An invalid OAuth2 application ID was used to authorize or authenticate with
+    InvalidClientId,
+    /// An invalid OAuth2 application origin was used to authorize or authenticate with
+    InvalidOrigin,
+    /// An invalid OAuth2 token was used to authorize or authenticate with
+    InvalidToken,
+    /// The specified user ID was invalid
+    InvalidUser,
+    /// A standard OAuth2 error occurred; check the data object for the OAuth2 error details
+    OAuth2Error,
+    /// An asynchronous SELECT_TEXT_CHANNEL/SELECT_VOICE_CHANNEL command timed out
+    SelectChannelTimedOut,
+    /// An asynchronous GET_GUILD command timed out
+    GetGuildTimedOut,
+    /// You tried to join a user to a voice channel but the user was already in one
+    SelectVoiceForceRequired,
+    /// You tried to capture more than one shortcut key at once
+    CaptureShortcutAlreadyListening,
+    /// An error code that is not documented
+    Unknown(u32),
+}
+
+impl From<u32> for RpcErrorCode {
+    fn from(code: u32) -> Self {
+        match code {
+            1000 => Self::UnknownError,
+            4000 => Self::InvalidPayload,
+            4002 => Self::InvalidCommand,
+            4003 => Self::InvalidGuild,
+            4004 => Self::InvalidEvent,
+            4005 => Self::InvalidChannel,
+            4006 => Self::InvalidPermissions,
+            4007 => Self::InvalidClientId,
+            4008 => Self::InvalidOrigin,
+            4009 => Self::InvalidToken,
+            4010 => Self::InvalidUser,
+            5000 => Self::OAuth2Error,
+            5001 => Self::SelectChannelTimedOut,
+            5002 => Self::GetGuildTimedOut,
+            5003 => Self::SelectVoiceForceRequired,
+            5004 => Self::CaptureShortcutAlreadyListening,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<RpcErrorCode> for u32 {
+    fn from(code: RpcErrorCode) -> Self {
+        match code {
+            RpcErrorCode::UnknownError => 1000,
+            RpcErrorCode::InvalidPayload => 4000,
+            RpcErrorCode::InvalidCommand => 4002,
+            RpcErrorCode::InvalidGuild => 4003,
+            RpcErrorCode::InvalidEvent => 4004,
+            RpcErrorCode::InvalidChannel => 4005,
+            RpcErrorCode::InvalidPermissions => 4006,
+            RpcErrorCode::InvalidClientId => 4007,
+            RpcErrorCode::InvalidOrigin => 4008,
+            RpcErrorCode::InvalidToken => 4009,
+            RpcErrorCode::InvalidUser => 4010,
+            RpcErrorCode::OAuth2Error => 5000,
+            RpcErrorCode::SelectChannelTimedOut => 5001,
+            RpcErrorCode::GetGuildTimedOut => 5002,
+            RpcErrorCode::SelectVoiceForceRequired => 5003,
+            RpcErrorCode::CaptureShortcutAlreadyListening => 5004,
+            RpcErrorCode::Unknown(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for RpcErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(code) => write!(f, "{}", code),
+            known => write!(f, "{:?} ({})", known, u32::from(*known)),
+        }
+    }
+}
+
+/// <https://discord.com/developers/docs/topics/opcodes-and-status-codes#rpc-rpc-close-event-codes>
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(from = "u32", into = "u32")]
+pub enum RpcCloseCode {
+    /// You connected to the RPC server with an invalid client ID
+    InvalidClientId,
+    /// You connected to the RPC server with an invalid origin
+    InvalidOrigin,
+    /// You are being rate limited
+    RateLimited,
+    /// The OAuth2 token associated with a connection was revoked, get a new one!
+    TokenRevoked,
+    /// The RPC Server version specified in the connection string was not valid
+    InvalidVersion,
+    /// The encoding specified in the connection string was not valid
+    InvalidEncoding,
+    /// A close code that is not documented
+    Unknown(u32),
+}
+
+impl From<u32> for RpcCloseCode {
+    fn from(code: u32) -> Self {
+        match code {
+            4000 => Self::InvalidClientId,
+            4001 => Self::InvalidOrigin,
+            4002 => Self::RateLimited,
+            4003 => Self::TokenRevoked,
+            4004 => Self::InvalidVersion,
+            4005 => Self::InvalidEncoding,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<RpcCloseCode> for u32 {
+    fn from(code: RpcCloseCode) -> Self {
+        match code {
+            RpcCloseCode::InvalidClientId => 4000,
+            RpcCloseCode::InvalidOrigin => 4001,
+            RpcCloseCode::RateLimited => 4002,
+            RpcCloseCode::TokenRevoked => 4003,
+            RpcCloseCode::InvalidVersion => 4004,
+            RpcCloseCode::InvalidEncoding => 4005,
+            RpcCloseCode::Unknown(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for RpcCloseCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(code) => write!(f, "{}", code),
+            known => write!(f, "{:?} ({})", known, u32::from(*known)),
+        }
+    }
+}
@@ -1,12 +1,18 @@
 use crate::models::shared::{
-    Channel, Guild, User,
+    Channel, Guild, Scope, User,
+    activity::{
+        ActivityAssets, ActivityParty, ActivitySecrets, ActivityTimestamps, ActivityType,
+        StatusDisplayType,
+    },
     voice::{SoundboardSound, UserVoiceSettings, VoiceSettings},
 };
 
+use std::time::SystemTime;
+
 use serde::{Deserialize, Serialize};
 
 /// <https://discord.com/developers/docs/topics/rpc#commands-and-events-rpc-commands>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "cmd", content = "data")]
 pub enum ReturnedCommand {
     Authorize { code: String },
@@ -24,7 +30,7 @@ pub enum ReturnedCommand {
     GetVoiceSettings(VoiceSettings),
     SetVoiceSettings(VoiceSettings),
     SetCertifiedDevices, // Restricted to hardware manufacturers
-    SetActivity,         // TODO
+    SetActivity(Option<Box<SetActivityData>>),
     SendActivityJoinInvite,
     CloseActivityRequest,
     ToggleVideo,
@@ -34,7 +40,7 @@ pub enum ReturnedCommand {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#authenticate-oauth2-application-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OAuth2Application {
     /// string - application description
     pub description: String,
@@ -49,20 +55,53 @@ pub struct OAuth2Application {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#authenticate-authenticate-response-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AuthenticateData {
     /// partial user object - the authed user
     pub user: User,
     /// array of OAuth2 scopes - authorized scopes
-    pub scopes: Vec<String>,
+    pub scopes: Vec<Scope>,
     /// date - expiration date of OAuth2 token
-    pub expires: String,
+    #[serde(with = "super::date")]
+    pub expires: SystemTime,
     /// OAuth2 application object - application the user authorized
     pub application: OAuth2Application,
 }
 
+/// <https://discord.com/developers/docs/topics/rpc#setactivity-example-set-activity-response-payload>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetActivityData {
+    /// string - name of the application
+    pub name: Option<String>,
+    /// snowflake - application id
+    pub application_id: Option<String>,
+    /// ?string - user's current party status
+    pub state: Option<String>,
+    /// ?string - what the player is currently doing
+    pub details: Option<String>,
+    /// timestamps object - unix timestamps for start and/or end of the game
+    pub timestamps: Option<ActivityTimestamps>,
+    /// assets object - images for the presence and their hover texts
+    pub assets: Option<ActivityAssets>,
+    /// party object - information for the current party of the player
+    pub party: Option<ActivityParty>,
+    /// secrets object - secrets for Rich Presence joining and spectating
+    pub secrets: Option<ActivitySecrets>,
+    /// array of strings - labels of the custom buttons shown in the Rich Presence
+    pub buttons: Option<Vec<String>>,
+    /// integer - activity type
+    #[serde(rename = "type")]
+    pub activity_type: Option<ActivityType>,
+    /// boolean - whether or not the activity is an instanced game session
+    pub instance: Option<bool>,
+    /// integer - status display type
+    pub status_display_type: Option<StatusDisplayType>,
+    /// object - additional metadata, such as the URLs of the custom buttons
+    pub metadata: Option<serde_json::Value>,
+}
+
 /// <https://discord.com/developers/docs/topics/rpc#getguild-get-guild-response-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GetGuildData {
     /// string - guild id
     pub id: String,
@@ -1,9 +1,11 @@
+use super::codes::{RpcCloseCode, RpcErrorCode};
+
 use crate::models::shared::{Guild, Message, User, voice::VoicePan};
 
 use serde::{Deserialize, Serialize};
 
 /// <https://discord.com/developers/docs/topics/rpc#ready-rpc-server-configuration-object>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReadyConfig {
     /// string - server's cdn
     pub cdn_host: String,
@@ -14,7 +16,7 @@ pub struct ReadyConfig {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#ready-ready-dispatch-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReadyData {
     #[serde(rename = "v")]
     /// integer - RPC version
@@ -26,16 +28,26 @@ pub struct ReadyData {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#error-error-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ErrorData {
     /// integer - RPC Error Code
-    pub code: u32,
+    pub code: RpcErrorCode,
     /// string - Error description
     pub message: String,
 }
 
+/// <https://discord.com/developers/docs/topics/opcodes-and-status-codes#rpc-rpc-close-event-codes>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CloseData {
+    /// integer - RPC close code
+    pub code: RpcCloseCode,
+    /// string - reason for closing the connection
+    #[serde(default)]
+    pub message: String,
+}
+
 /// <https://discord.com/developers/docs/topics/rpc#guildstatus-guild-status-dispatch-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GuildStatusData {
     /// partial guild object - guild with requested id
     pub guild: Guild,
@@ -44,7 +56,7 @@ pub struct GuildStatusData {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#guildcreate-guild-create-dispatch-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GuildCreateData {
     /// string - guild id
     pub id: String,
@@ -53,7 +65,7 @@ pub struct GuildCreateData {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#channelcreate-channel-create-dispatch-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChannelCreateData {
     /// string - channel id
     pub id: String,
@@ -65,7 +77,7 @@ pub struct ChannelCreateData {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#voicechannelselect-voice-channel-select-dispatch-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VoiceChannelSelectData {
     /// string - id of channel (null if none)
     pub channel_id: Option<String>,
@@ -74,7 +86,7 @@ pub struct VoiceChannelSelectData {
 }
 
 /// <https://discord.com/developers/docs/resources/voice#voice-state-object-voice-state-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VoiceState {
     /// boolean - whether this user is deafened by the server
     pub deaf: bool,
@@ -93,7 +105,7 @@ pub struct VoiceState {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#voicestatecreatevoicestateupdatevoicestatedelete-example-voice-state-dispatch-payload>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VoiceStateData {
     /// voice state object
     #[serde(rename = "voice_state")]
@@ -111,7 +123,7 @@ pub struct VoiceStateData {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#voiceconnectionstatus-voice-connection-states>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum VoiceConnectionState {
     Disconnected,
@@ -127,7 +139,7 @@ pub enum VoiceConnectionState {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#voiceconnectionstatus-voice-connection-status-dispatch-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VoiceConnectionStatusData {
     /// string - one of the voice connection states listed below
     pub state: VoiceConnectionState,
@@ -142,7 +154,7 @@ pub struct VoiceConnectionStatusData {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#messagecreatemessageupdatemessagedelete-message-argument-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MessageData {
     /// string - channel id
     pub channel_id: String,
@@ -151,14 +163,14 @@ pub struct MessageData {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#speakingstartspeakingstop-speaking-dispatch-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SpeakingData {
     /// string - id of user who started/stopped speaking
     pub user_id: String,
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#notificationcreate-notification-create-dispatch-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct NotificationCreateData {
     /// string - id of channel where notification occurred
     pub channel_id: String,
@@ -173,33 +185,33 @@ pub struct NotificationCreateData {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#activityjoin-activity-join-dispatch-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ActivityJoinData {
     /// string - the join_secret for the given invite
     pub secret: String,
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#activityspectate-activity-spectate-dispatch-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ActivitySpectateData {
     /// string - the spectate_secret for the given invite
     pub secret: String,
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#activityjoinrequest-activity-join-request-data-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ActivityJoinRequestData {
     /// partial user object - information about the user requesting to join
     pub user: User,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VideoStateData {
     /// boolean - whether the user is currently streaming video
     pub active: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ScreenshareStateData {
     /// boolean - whether the user is currently screensharing
     pub active: bool,
@@ -0,0 +1,42 @@
+//! (De)serializes the RFC 3339 dates sent by the RPC server, such as `2099-01-01T00:00:00.000Z`
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&humantime::format_rfc3339_millis(*time))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+    let date = String::deserialize(deserializer)?;
+    humantime::parse_rfc3339(&date).map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use serde_json::Value;
+
+    #[test]
+    fn dates_round_trip() {
+        let date = Value::String("2099-01-01T00:00:00.500Z".to_owned());
+
+        let time = super::deserialize(&date).unwrap();
+        let serialized = super::serialize(&time, serde_json::value::Serializer).unwrap();
+
+        assert_eq!(
+            time,
+            SystemTime::UNIX_EPOCH + Duration::from_millis(4_070_908_800_500)
+        );
+        assert_eq!(serialized, date);
+    }
+
+    #[test]
+    fn invalid_dates_are_rejected() {
+        for date in ["", "2099-01-01", "2099-02-29T00:00:00Z", "tomorrow"] {
+            assert!(super::deserialize(&Value::String(date.to_owned())).is_err());
+        }
+    }
+}
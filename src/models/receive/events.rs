@@ -5,7 +5,7 @@ use crate::models::shared::voice::VoiceSettings;
 use serde::{Deserialize, Serialize};
 
 /// <https://discord.com/developers/docs/topics/rpc#commands-and-events-rpc-events>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "evt", content = "data")]
 pub enum ReturnedEvent {
     Ready(ReadyData),
@@ -31,3 +31,33 @@ pub enum ReturnedEvent {
     VideoStateUpdate(VideoStateData),
     ScreenshareStateUpdate(ScreenshareStateData),
 }
+
+impl ReturnedEvent {
+    /// Returns the name of the event as sent by the RPC server
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ready(_) => "READY",
+            Self::Error(_) => "ERROR",
+            Self::GuildStatus(_) => "GUILD_STATUS",
+            Self::GuildCreate(_) => "GUILD_CREATE",
+            Self::ChannelCreate(_) => "CHANNEL_CREATE",
+            Self::VoiceChannelSelect(_) => "VOICE_CHANNEL_SELECT",
+            Self::VoiceSettingsUpdate(_) => "VOICE_SETTINGS_UPDATE",
+            Self::VoiceStateCreate(_) => "VOICE_STATE_CREATE",
+            Self::VoiceStateUpdate(_) => "VOICE_STATE_UPDATE",
+            Self::VoiceStateDelete(_) => "VOICE_STATE_DELETE",
+            Self::VoiceConnectionStatus(_) => "VOICE_CONNECTION_STATUS",
+            Self::MessageCreate(_) => "MESSAGE_CREATE",
+            Self::MessageUpdate(_) => "MESSAGE_UPDATE",
+            Self::MessageDelete(_) => "MESSAGE_DELETE",
+            Self::SpeakingStart(_) => "SPEAKING_START",
+            Self::SpeakingStop(_) => "SPEAKING_STOP",
+            Self::NotificationCreate(_) => "NOTIFICATION_CREATE",
+            Self::ActivityJoin(_) => "ACTIVITY_JOIN",
+            Self::ActivitySpectate(_) => "ACTIVITY_SPECTATE",
+            Self::ActivityJoinRequest(_) => "ACTIVITY_JOIN_REQUEST",
+            Self::VideoStateUpdate(_) => "VIDEO_STATE_UPDATE",
+            Self::ScreenshareStateUpdate(_) => "SCREENSHARE_STATE_UPDATE",
+        }
+    }
+}
@@ -1,13 +1,31 @@
+use crate::models::shared::User;
+
+pub mod codes;
 pub mod commands;
 pub mod events;
 
 mod data;
+mod date;
 
 /// Represents values received from the RPC server, either events or command responses
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum ReceivedItem {
     Event(Box<events::ReturnedEvent>),
     Command(Box<commands::ReturnedCommand>),
     SocketClosed,
+    /// The RPC server closed the connection with the given reason, followed by [`ReceivedItem::SocketClosed`]
+    #[serde(skip)]
+    Closed(events::CloseData),
+    /// The connection was lost and the client is about to make the given reconnection attempt
+    #[serde(skip)]
+    Reconnecting {
+        attempt: u32,
+    },
+    /// The client reconnected to the RPC server as the given user and is restoring its state
+    #[serde(skip)]
+    Reconnected(User),
+    /// The event stream fell behind and skipped the given number of values
+    #[serde(skip)]
+    Lagged(u64),
 }
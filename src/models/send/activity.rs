@@ -0,0 +1,393 @@
+use crate::models::shared::activity::{
+    Activity, ActivityAssets, ActivityButton, ActivityParty, ActivitySecrets, ActivityTimestamps,
+    ActivityType, StatusDisplayType,
+};
+
+/// Maximum number of buttons that can be shown in a Rich Presence
+const MAX_BUTTONS: usize = 2;
+
+/// Reasons an activity is rejected by [`ActivityBuilder::build`] before it is sent to the RPC server
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum ActivityValidationError {
+    #[error("{field} must be between {min} and {max} characters long, but was {length}")]
+    InvalidLength {
+        field: &'static str,
+        min: usize,
+        max: usize,
+        length: usize,
+    },
+    #[error("An activity can have at most {MAX_BUTTONS} buttons, but {0} were provided")]
+    TooManyButtons(usize),
+    #[error("An activity cannot have both buttons and secrets")]
+    ButtonsWithSecrets,
+    #[error("The party size ({current}) must be between 1 and the maximum size ({max})")]
+    InvalidPartySize { current: u32, max: u32 },
+    #[error("The end timestamp must not be before the start timestamp")]
+    InvalidTimestamps,
+}
+
+/// Builds an [`Activity`], checking it against the limits enforced by Discord
+#[derive(Debug, Clone, Default)]
+pub struct ActivityBuilder {
+    activity: Activity,
+}
+
+impl ActivityBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The user's current party status (2-128 characters)
+    pub fn state(mut self, state: impl Into<String>) -> Self {
+        self.activity.state = Some(state.into());
+        self
+    }
+
+    /// URL that is linked when clicking on the state text
+    pub fn state_url(mut self, url: impl Into<String>) -> Self {
+        self.activity.state_url = Some(url.into());
+        self
+    }
+
+    /// What the player is currently doing (2-128 characters)
+    pub fn details(mut self, details: impl Into<String>) -> Self {
+        self.activity.details = Some(details.into());
+        self
+    }
+
+    /// URL that is linked when clicking on the details text
+    pub fn details_url(mut self, url: impl Into<String>) -> Self {
+        self.activity.details_url = Some(url.into());
+        self
+    }
+
+    /// Unix time (in milliseconds) of when the activity started
+    pub fn start_timestamp(mut self, start: u64) -> Self {
+        self.timestamps().start = Some(start);
+        self
+    }
+
+    /// Unix time (in milliseconds) of when the activity ends
+    pub fn end_timestamp(mut self, end: u64) -> Self {
+        self.timestamps().end = Some(end);
+        self
+    }
+
+    /// Asset key or URL of the large image (1-256 characters)
+    pub fn large_image(mut self, image: impl Into<String>) -> Self {
+        self.assets().large_image = Some(image.into());
+        self
+    }
+
+    /// Text displayed when hovering over the large image (2-128 characters)
+    pub fn large_text(mut self, text: impl Into<String>) -> Self {
+        self.assets().large_text = Some(text.into());
+        self
+    }
+
+    /// URL that is opened when clicking on the large image
+    pub fn large_url(mut self, url: impl Into<String>) -> Self {
+        self.assets().large_url = Some(url.into());
+        self
+    }
+
+    /// Asset key or URL of the small image (1-256 characters)
+    pub fn small_image(mut self, image: impl Into<String>) -> Self {
+        self.assets().small_image = Some(image.into());
+        self
+    }
+
+    /// Text displayed when hovering over the small image (2-128 characters)
+    pub fn small_text(mut self, text: impl Into<String>) -> Self {
+        self.assets().small_text = Some(text.into());
+        self
+    }
+
+    /// URL that is opened when clicking on the small image
+    pub fn small_url(mut self, url: impl Into<String>) -> Self {
+        self.assets().small_url = Some(url.into());
+        self
+    }
+
+    /// ID of the party (1-128 characters)
+    pub fn party_id(mut self, id: impl Into<String>) -> Self {
+        self.party().id = Some(id.into());
+        self
+    }
+
+    /// Current and maximum size of the party
+    pub fn party_size(mut self, current: u32, max: u32) -> Self {
+        self.party().size = Some([current, max]);
+        self
+    }
+
+    /// Secret for joining a party (1-128 characters)
+    pub fn join_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secrets().join = Some(secret.into());
+        self
+    }
+
+    /// Secret for spectating a game (1-128 characters)
+    pub fn spectate_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secrets().spectate = Some(secret.into());
+        self
+    }
+
+    /// Secret for a specific instanced match (1-128 characters)
+    pub fn match_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secrets().match_secret = Some(secret.into());
+        self
+    }
+
+    /// Add a custom button with a label (1-32 characters) and a URL (1-512 characters)
+    pub fn button(mut self, label: impl Into<String>, url: impl Into<String>) -> Self {
+        self.activity
+            .buttons
+            .get_or_insert_with(Vec::new)
+            .push(ActivityButton {
+                label: label.into(),
+                url: url.into(),
+            });
+        self
+    }
+
+    pub fn activity_type(mut self, activity_type: ActivityType) -> Self {
+        self.activity.activity_type = Some(activity_type);
+        self
+    }
+
+    /// Whether or not the activity is an instanced game session
+    pub fn instance(mut self, instance: bool) -> Self {
+        self.activity.instance = Some(instance);
+        self
+    }
+
+    /// Which field is displayed in the user's status text in the member list
+    pub fn status_display_type(mut self, status_display_type: StatusDisplayType) -> Self {
+        self.activity.status_display_type = Some(status_display_type);
+        self
+    }
+
+    /// Validate and return the activity
+    pub fn build(self) -> Result<Activity, ActivityValidationError> {
+        let activity = self.activity;
+
+        check_length("state", &activity.state, 2, 128)?;
+        check_length("details", &activity.details, 2, 128)?;
+
+        if let Some(timestamps) = &activity.timestamps
+            && let (Some(start), Some(end)) = (timestamps.start, timestamps.end)
+            && end < start
+        {
+            return Err(ActivityValidationError::InvalidTimestamps);
+        }
+
+        if let Some(assets) = &activity.assets {
+            check_length("large_image", &assets.large_image, 1, 256)?;
+            check_length("large_text", &assets.large_text, 2, 128)?;
+            check_length("small_image", &assets.small_image, 1, 256)?;
+            check_length("small_text", &assets.small_text, 2, 128)?;
+        }
+
+        if let Some(party) = &activity.party {
+            check_length("party id", &party.id, 1, 128)?;
+            if let Some([current, max]) = party.size
+                && (current == 0 || current > max)
+            {
+                return Err(ActivityValidationError::InvalidPartySize { current, max });
+            }
+        }
+
+        if let Some(secrets) = &activity.secrets {
+            check_length("join secret", &secrets.join, 1, 128)?;
+            check_length("spectate secret", &secrets.spectate, 1, 128)?;
+            check_length("match secret", &secrets.match_secret, 1, 128)?;
+        }
+
+        if let Some(buttons) = &activity.buttons {
+            if buttons.len() > MAX_BUTTONS {
+                return Err(ActivityValidationError::TooManyButtons(buttons.len()));
+            }
+            if activity.secrets.is_some() {
+                return Err(ActivityValidationError::ButtonsWithSecrets);
+            }
+            for button in buttons {
+                check_length("button label", &Some(&button.label), 1, 32)?;
+                check_length("button url", &Some(&button.url), 1, 512)?;
+            }
+        }
+
+        Ok(activity)
+    }
+
+    fn timestamps(&mut self) -> &mut ActivityTimestamps {
+        self.activity
+            .timestamps
+            .get_or_insert_with(ActivityTimestamps::default)
+    }
+
+    fn assets(&mut self) -> &mut ActivityAssets {
+        self.activity
+            .assets
+            .get_or_insert_with(ActivityAssets::default)
+    }
+
+    fn party(&mut self) -> &mut ActivityParty {
+        self.activity
+            .party
+            .get_or_insert_with(ActivityParty::default)
+    }
+
+    fn secrets(&mut self) -> &mut ActivitySecrets {
+        self.activity
+            .secrets
+            .get_or_insert_with(ActivitySecrets::default)
+    }
+}
+
+fn check_length<S: AsRef<str>>(
+    field: &'static str,
+    value: &Option<S>,
+    min: usize,
+    max: usize,
+) -> Result<(), ActivityValidationError> {
+    let Some(value) = value else {
+        return Ok(());
+    };
+    let length = value.as_ref().chars().count();
+    if length < min || length > max {
+        return Err(ActivityValidationError::InvalidLength {
+            field,
+            min,
+            max,
+            length,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_are_counted_in_characters() {
+        let state = "é".repeat(128);
+        assert_eq!(state.len(), 256);
+        let activity = ActivityBuilder::new().state(state.clone()).build().unwrap();
+        assert_eq!(activity.state, Some(state));
+
+        assert_eq!(
+            ActivityBuilder::new()
+                .state("é".repeat(129))
+                .build()
+                .unwrap_err(),
+            ActivityValidationError::InvalidLength {
+                field: "state",
+                min: 2,
+                max: 128,
+                length: 129,
+            }
+        );
+    }
+
+    #[test]
+    fn short_fields_are_rejected() {
+        assert_eq!(
+            ActivityBuilder::new().details("a").build().unwrap_err(),
+            ActivityValidationError::InvalidLength {
+                field: "details",
+                min: 2,
+                max: 128,
+                length: 1,
+            }
+        );
+        assert!(ActivityBuilder::new().details("ab").build().is_ok());
+        assert!(matches!(
+            ActivityBuilder::new().large_image("").build(),
+            Err(ActivityValidationError::InvalidLength {
+                field: "large_image",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn button_labels_are_limited_to_32_characters() {
+        let url = "https://example.com";
+        assert!(
+            ActivityBuilder::new()
+                .button("a".repeat(32), url)
+                .build()
+                .is_ok()
+        );
+        assert!(matches!(
+            ActivityBuilder::new().button("a".repeat(33), url).build(),
+            Err(ActivityValidationError::InvalidLength {
+                field: "button label",
+                length: 33,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn at_most_two_buttons_are_allowed() {
+        let builder = ActivityBuilder::new()
+            .button("First", "https://example.com/1")
+            .button("Second", "https://example.com/2");
+        assert!(builder.clone().build().is_ok());
+
+        assert_eq!(
+            builder
+                .button("Third", "https://example.com/3")
+                .build()
+                .unwrap_err(),
+            ActivityValidationError::TooManyButtons(3)
+        );
+    }
+
+    #[test]
+    fn buttons_cannot_be_combined_with_secrets() {
+        assert_eq!(
+            ActivityBuilder::new()
+                .button("Watch", "https://example.com")
+                .join_secret("secret")
+                .build()
+                .unwrap_err(),
+            ActivityValidationError::ButtonsWithSecrets
+        );
+    }
+
+    #[test]
+    fn party_size_must_be_between_one_and_the_maximum() {
+        assert!(ActivityBuilder::new().party_size(4, 4).build().is_ok());
+        assert_eq!(
+            ActivityBuilder::new().party_size(0, 4).build().unwrap_err(),
+            ActivityValidationError::InvalidPartySize { current: 0, max: 4 }
+        );
+        assert_eq!(
+            ActivityBuilder::new().party_size(5, 4).build().unwrap_err(),
+            ActivityValidationError::InvalidPartySize { current: 5, max: 4 }
+        );
+    }
+
+    #[test]
+    fn end_timestamp_must_not_be_before_start() {
+        assert!(
+            ActivityBuilder::new()
+                .start_timestamp(1000)
+                .end_timestamp(1000)
+                .build()
+                .is_ok()
+        );
+        assert_eq!(
+            ActivityBuilder::new()
+                .start_timestamp(1000)
+                .end_timestamp(999)
+                .build()
+                .unwrap_err(),
+            ActivityValidationError::InvalidTimestamps
+        );
+    }
+}
@@ -1,24 +1,32 @@
-use crate::models::shared::voice::{UserVoiceSettings, VoiceSettings};
+use crate::models::shared::{
+    Scope,
+    activity::Activity,
+    voice::{UserVoiceSettings, VoiceSettings},
+};
 
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
 /// <https://discord.com/developers/docs/topics/rpc#authorize-authorize-argument-structure>
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AuthorizeArgs {
     /// array of OAuth2 scopes - scopes to authorize
-    pub scopes: Vec<String>,
+    pub scopes: Vec<Scope>,
     /// string - OAuth2 application ID
     pub client_id: String,
     /// string - one-time use RPC token
     pub rpc_token: Option<String>,
     /// string - username to create a guest account with if the user does not have Discord
     pub username: Option<String>,
+    /// string - PKCE code challenge derived from the code verifier, for public clients without a client secret
+    pub code_challenge: Option<String>,
+    /// string - method used to derive the code challenge, which must be S256
+    pub code_challenge_method: Option<String>,
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#authenticate-authenticate-argument-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AuthenticateArgs {
     /// string - OAuth2 access token
     pub access_token: String,
@@ -26,7 +34,7 @@ pub struct AuthenticateArgs {
 
 /// <https://discord.com/developers/docs/topics/rpc#getguild-get-guild-argument-structure>
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GetGuildArgs {
     /// string - id of the guild to get
     pub guild_id: String,
@@ -35,14 +43,14 @@ pub struct GetGuildArgs {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#getchannel-get-channel-argument-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GetChannelArgs {
     /// string - id of the channel to get
     pub channel_id: String,
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#getchannels-get-channels-argument-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GetChannelsArgs {
     /// string - id of the guild to get channels for
     pub guild_id: String,
@@ -53,7 +61,7 @@ pub type SetUserVoiceSettingsArgs = UserVoiceSettings;
 
 /// <https://discord.com/developers/docs/topics/rpc#selectvoicechannel-select-voice-channel-argument-structure>
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SelectVoiceChannelArgs {
     /// string - channel id to join (or null to leave)
     #[serialize_always]
@@ -68,7 +76,7 @@ pub struct SelectVoiceChannelArgs {
 
 /// <https://discord.com/developers/docs/topics/rpc#selecttextchannel-select-text-channel-argument-structure>
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SelectTextChannelArgs {
     /// string - channel id to join (or null to leave)
     #[serialize_always]
@@ -80,15 +88,26 @@ pub struct SelectTextChannelArgs {
 /// <https://discord.com/developers/docs/topics/rpc#setvoicesettings-set-voice-settings-argument-and-response-structure>
 pub type SetVoiceSettingsArgs = VoiceSettings;
 
+/// <https://discord.com/developers/docs/topics/rpc#setactivity-set-activity-argument-structure>
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetActivityArgs {
+    /// integer - the application's process id
+    pub pid: u32,
+    /// activity object - the rich presence to assign to the user (or null to clear it)
+    #[serialize_always]
+    pub activity: Option<Activity>,
+}
+
 /// <https://discord.com/developers/docs/topics/rpc#sendactivityjoininvite-example-send-activity-join-invite-payload>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SendActivityJoinInviteArgs {
     /// snowflake - the id of the requesting user
     pub user_id: String,
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#closeactivityrequest-close-activity-request-argument-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CloseActivityRequestArgs {
     /// snowflake - the id of the requesting user
     pub user_id: String,
@@ -96,14 +115,14 @@ pub struct CloseActivityRequestArgs {
 
 /// <https://github.com/Khaomi/discord-rpc/blob/3a2d794abb74239f6608565a520f29d0c6e98247/src/structures/ClientUser.ts#L397>
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ToggleScreenshareArgs {
     /// integer - PID of the application to be shared, if any
     pub pid: Option<u32>,
 }
 
 /// <https://github.com/Khaomi/discord-rpc/blob/3a2d794abb74239f6608565a520f29d0c6e98247/src/structures/ClientUser.ts#L376>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PlaySoundboardSoundArgs {
     /// string - the id of the guild to play the sound in
     pub guild_id: String,
@@ -3,12 +3,13 @@ pub use super::args::*;
 use super::events::SubscribeableEvent;
 
 use crate::Result;
+use crate::models::shared::Scope;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 /// <https://discord.com/developers/docs/topics/rpc#commands-and-events-rpc-commands>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "cmd", content = "args")]
 pub enum SentCommand {
     Dispatch(SubscribeableEvent), // ???
@@ -27,7 +28,7 @@ pub enum SentCommand {
     GetVoiceSettings,
     SetVoiceSettings(SetVoiceSettingsArgs),
     SetCertifiedDevices, // Restricted to hardware manufacturers
-    SetActivity,         // TODO
+    SetActivity(Box<SetActivityArgs>),
     SendActivityJoinInvite(SendActivityJoinInviteArgs),
     CloseActivityRequest(CloseActivityRequestArgs),
     ToggleVideo,
@@ -37,6 +38,40 @@ pub enum SentCommand {
 }
 
 impl SentCommand {
+    /// Returns the OAuth2 scopes needed to send the command, as far as they are documented
+    /// AUTHORIZE, AUTHENTICATE and the Rich Presence commands need no authentication
+    pub fn required_scopes(&self) -> Vec<Scope> {
+        match self {
+            Self::Dispatch(_)
+            | Self::Authorize(_)
+            | Self::Authenticate(_)
+            | Self::SetActivity(_)
+            | Self::SendActivityJoinInvite(_)
+            | Self::CloseActivityRequest(_) => vec![],
+            Self::Subscribe(event) | Self::Unsubscribe(event) => event.required_scopes(),
+            Self::GetVoiceSettings => vec![Scope::Rpc, Scope::RpcVoiceRead],
+            Self::SetUserVoiceSettings(_) | Self::SetVoiceSettings(_) => {
+                vec![Scope::Rpc, Scope::RpcVoiceWrite]
+            }
+            Self::ToggleVideo => vec![Scope::Rpc, Scope::RpcVideoWrite],
+            Self::ToggleScreenshare(_) => vec![Scope::Rpc, Scope::RpcScreenshareWrite],
+            _ => vec![Scope::Rpc],
+        }
+    }
+
+    /// Returns the scopes needed to send the command that are not among the granted scopes
+    pub fn missing_scopes(&self, granted: &[Scope]) -> Vec<Scope> {
+        self.required_scopes()
+            .into_iter()
+            .filter(|scope| !granted.contains(scope))
+            .collect()
+    }
+
+    /// Whether the granted scopes are enough to send the command
+    pub fn is_permitted_by(&self, granted: &[Scope]) -> bool {
+        self.missing_scopes(granted).is_empty()
+    }
+
     pub(crate) fn to_json(&self) -> Result<Value> {
         let command_json = match self {
             Self::Dispatch(event) => {
@@ -1,7 +1,10 @@
+use crate::models::receive::events::ReturnedEvent;
+use crate::models::shared::Scope;
+
 use serde::{Deserialize, Serialize};
 
 /// <https://discord.com/developers/docs/topics/rpc#commands-and-events-rpc-events>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "evt", content = "args")]
 pub enum SubscribeableEvent {
     GuildStatus { guild_id: String },
@@ -25,3 +28,83 @@ pub enum SubscribeableEvent {
     VideoStateUpdate,
     ScreenshareStateUpdate,
 }
+
+impl SubscribeableEvent {
+    /// Returns the name of the event as sent to the RPC server
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::GuildStatus { .. } => "GUILD_STATUS",
+            Self::GuildCreate => "GUILD_CREATE",
+            Self::ChannelCreate => "CHANNEL_CREATE",
+            Self::VoiceChannelSelect => "VOICE_CHANNEL_SELECT",
+            Self::VoiceStateCreate { .. } => "VOICE_STATE_CREATE",
+            Self::VoiceStateUpdate { .. } => "VOICE_STATE_UPDATE",
+            Self::VoiceStateDelete { .. } => "VOICE_STATE_DELETE",
+            Self::VoiceSettingsUpdate => "VOICE_SETTINGS_UPDATE",
+            Self::VoiceConnectionStatus => "VOICE_CONNECTION_STATUS",
+            Self::SpeakingStart { .. } => "SPEAKING_START",
+            Self::SpeakingStop { .. } => "SPEAKING_STOP",
+            Self::MessageCreate { .. } => "MESSAGE_CREATE",
+            Self::MessageUpdate { .. } => "MESSAGE_UPDATE",
+            Self::MessageDelete { .. } => "MESSAGE_DELETE",
+            Self::NotificationCreate => "NOTIFICATION_CREATE",
+            Self::ActivityJoin => "ACTIVITY_JOIN",
+            Self::ActivitySpectate => "ACTIVITY_SPECTATE",
+            Self::ActivityJoinRequest => "ACTIVITY_JOIN_REQUEST",
+            Self::VideoStateUpdate => "VIDEO_STATE_UPDATE",
+            Self::ScreenshareStateUpdate => "SCREENSHARE_STATE_UPDATE",
+        }
+    }
+
+    /// Whether the event was dispatched for this subscription, comparing the channel or guild ID where the payload carries it
+    pub fn matches(&self, event: &ReturnedEvent) -> bool {
+        if event.name() != self.name() {
+            return false;
+        }
+        match (self, event) {
+            (Self::GuildStatus { guild_id }, ReturnedEvent::GuildStatus(data)) => {
+                data.guild.id == *guild_id
+            }
+            (
+                Self::MessageCreate { channel_id }
+                | Self::MessageUpdate { channel_id }
+                | Self::MessageDelete { channel_id },
+                ReturnedEvent::MessageCreate(data)
+                | ReturnedEvent::MessageUpdate(data)
+                | ReturnedEvent::MessageDelete(data),
+            ) => data.channel_id == *channel_id,
+            _ => true,
+        }
+    }
+
+    /// Whether the payloads of the event tell apart subscriptions to it with different arguments
+    /// Voice state and speaking events do not carry the channel ID they were subscribed with
+    pub fn payload_identifies_arguments(&self) -> bool {
+        !matches!(
+            self,
+            Self::VoiceStateCreate { .. }
+                | Self::VoiceStateUpdate { .. }
+                | Self::VoiceStateDelete { .. }
+                | Self::SpeakingStart { .. }
+                | Self::SpeakingStop { .. }
+        )
+    }
+
+    /// Returns the OAuth2 scopes needed to subscribe to the event, as far as they are documented
+    /// The activity events belong to Rich Presence, which needs no authentication
+    pub fn required_scopes(&self) -> Vec<Scope> {
+        match self {
+            Self::ActivityJoin | Self::ActivitySpectate | Self::ActivityJoinRequest => vec![],
+            Self::VoiceSettingsUpdate => vec![Scope::Rpc, Scope::RpcVoiceRead],
+            Self::MessageCreate { .. }
+            | Self::MessageUpdate { .. }
+            | Self::MessageDelete { .. } => {
+                vec![Scope::Rpc, Scope::MessagesRead]
+            }
+            Self::NotificationCreate => vec![Scope::Rpc, Scope::RpcNotificationsRead],
+            Self::VideoStateUpdate => vec![Scope::Rpc, Scope::RpcVideoRead],
+            Self::ScreenshareStateUpdate => vec![Scope::Rpc, Scope::RpcScreenshareRead],
+            _ => vec![Scope::Rpc],
+        }
+    }
+}
@@ -1,4 +1,7 @@
 pub mod commands;
 pub mod events;
 
+mod activity;
 mod args;
+
+pub use activity::{ActivityBuilder, ActivityValidationError};
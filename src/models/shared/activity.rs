@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde_with::skip_serializing_none;
+
+/// <https://discord.com/developers/docs/events/gateway-events#activity-object-activity-types>
+#[derive(Serialize_repr, Deserialize_repr, Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum ActivityType {
+    /// Playing {name}
+    Playing = 0,
+    /// Streaming {details}
+    Streaming = 1,
+    /// Listening to {name}
+    Listening = 2,
+    /// Watching {name}
+    Watching = 3,
+    /// {emoji} {state}
+    Custom = 4,
+    /// Competing in {name}
+    Competing = 5,
+}
+
+/// <https://discord.com/developers/docs/events/gateway-events#activity-object-status-display-types>
+#[derive(Serialize_repr, Deserialize_repr, Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum StatusDisplayType {
+    /// the name of the activity is displayed in the status
+    Name = 0,
+    /// the state of the activity is displayed in the status
+    State = 1,
+    /// the details of the activity are displayed in the status
+    Details = 2,
+}
+
+/// <https://discord.com/developers/docs/events/gateway-events#activity-object-activity-timestamps>
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ActivityTimestamps {
+    /// integer - unix time (in milliseconds) of when the activity started
+    pub start: Option<u64>,
+    /// integer - unix time (in milliseconds) of when the activity ends
+    pub end: Option<u64>,
+}
+
+/// <https://discord.com/developers/docs/events/gateway-events#activity-object-activity-assets>
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ActivityAssets {
+    /// string - asset key or URL of the large image
+    pub large_image: Option<String>,
+    /// string - text displayed when hovering over the large image of the activity
+    pub large_text: Option<String>,
+    /// string - URL that is opened when clicking on the large image
+    pub large_url: Option<String>,
+    /// string - asset key or URL of the small image
+    pub small_image: Option<String>,
+    /// string - text displayed when hovering over the small image of the activity
+    pub small_text: Option<String>,
+    /// string - URL that is opened when clicking on the small image
+    pub small_url: Option<String>,
+}
+
+/// <https://discord.com/developers/docs/events/gateway-events#activity-object-activity-party>
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ActivityParty {
+    /// string - ID of the party
+    pub id: Option<String>,
+    /// array of two integers (current_size, max_size) - used to show the party's current and maximum size
+    pub size: Option<[u32; 2]>,
+}
+
+/// <https://discord.com/developers/docs/events/gateway-events#activity-object-activity-secrets>
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ActivitySecrets {
+    /// string - secret for joining a party
+    pub join: Option<String>,
+    /// string - secret for spectating a game
+    pub spectate: Option<String>,
+    /// string - secret for a specific instanced match
+    #[serde(rename = "match")]
+    pub match_secret: Option<String>,
+}
+
+/// <https://discord.com/developers/docs/events/gateway-events#activity-object-activity-buttons>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActivityButton {
+    /// string - text shown on the button (1-32 characters)
+    pub label: String,
+    /// string - URL opened when clicking the button (1-512 characters)
+    pub url: String,
+}
+
+/// <https://discord.com/developers/docs/topics/rpc#setactivity-set-activity-argument-structure>
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Activity {
+    /// ?string - user's current party status, or text used for a custom status
+    pub state: Option<String>,
+    /// string - URL that is linked when clicking on the state text
+    pub state_url: Option<String>,
+    /// ?string - what the player is currently doing
+    pub details: Option<String>,
+    /// string - URL that is linked when clicking on the details text
+    pub details_url: Option<String>,
+    /// timestamps object - unix timestamps for start and/or end of the game
+    pub timestamps: Option<ActivityTimestamps>,
+    /// assets object - images for the presence and their hover texts
+    pub assets: Option<ActivityAssets>,
+    /// party object - information for the current party of the player
+    pub party: Option<ActivityParty>,
+    /// secrets object - secrets for Rich Presence joining and spectating
+    pub secrets: Option<ActivitySecrets>,
+    /// array of button objects - custom buttons shown in the Rich Presence (max 2)
+    pub buttons: Option<Vec<ActivityButton>>,
+    /// integer - activity type
+    #[serde(rename = "type")]
+    pub activity_type: Option<ActivityType>,
+    /// boolean - whether or not the activity is an instanced game session
+    pub instance: Option<bool>,
+    /// integer - status display type; controls which field is displayed in the user's status text in the member list
+    pub status_display_type: Option<StatusDisplayType>,
+}
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 /// <https://discord.com/developers/docs/resources/channel#channel-object-channel-types>
-#[derive(Serialize_repr, Deserialize_repr, Debug, PartialEq)]
+#[derive(Serialize_repr, Deserialize_repr, Debug, Clone, PartialEq)]
 #[repr(u32)]
 pub enum ChannelType {
     /// a text channel within a server
@@ -34,7 +34,7 @@ pub enum ChannelType {
 }
 
 /// <https://discord.com/developers/docs/resources/channel#channel-object-channel-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Channel {
     /// snowflake - the id of this channel
     pub id: String,
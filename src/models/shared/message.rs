@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 /// <https://discord.com/developers/docs/resources/message#message-object-message-types>
-#[derive(Serialize_repr, Deserialize_repr, Debug, PartialEq)]
+#[derive(Serialize_repr, Deserialize_repr, Debug, Clone, PartialEq)]
 #[repr(u32)]
 pub enum MessageType {
     Default = 0,
@@ -47,7 +47,7 @@ pub enum MessageType {
 }
 
 /// <https://discord.com/developers/docs/resources/message#message-object-message-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Message {
     /// snowflake - id of the message
     pub id: String,
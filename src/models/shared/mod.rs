@@ -1,11 +1,14 @@
 mod channel;
 mod guild;
 mod message;
+mod scope;
 mod user;
 
+pub mod activity;
 pub mod voice;
 
 pub use channel::{Channel, ChannelType};
 pub use guild::Guild;
 pub use message::{Message, MessageType};
+pub use scope::Scope;
 pub use user::User;
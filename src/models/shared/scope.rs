@@ -0,0 +1,152 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// <https://discord.com/developers/docs/topics/oauth2#shared-resources-oauth2-scopes>
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum Scope {
+    /// allows /users/@me/activities to return the user's activities
+    ActivitiesRead,
+    /// allows updating the user's activity
+    ActivitiesWrite,
+    /// allows your app to use commands in a guild
+    ApplicationsCommands,
+    /// for oauth2 bots, this puts the bot in the user's selected guild by default
+    Bot,
+    /// allows /users/@me/connections to return linked third-party accounts
+    Connections,
+    /// allows your app to see information about the user's DMs and group DMs
+    DmChannelsRead,
+    /// enables /users/@me to return an email
+    Email,
+    /// allows your app to join users to a group DM
+    GdmJoin,
+    /// allows /users/@me/guilds to return basic information about all of a user's guilds
+    Guilds,
+    /// allows /guilds/{guild.id}/members/{user.id} to be used for joining users to a guild
+    GuildsJoin,
+    /// allows /users/@me/guilds/{guild.id}/member to return a user's member information in a guild
+    GuildsMembersRead,
+    /// allows /users/@me without email
+    Identify,
+    /// for local rpc server api access, this allows you to read messages from all client channels
+    MessagesRead,
+    /// allows your app to know a user's friends and implicit relationships
+    RelationshipsRead,
+    /// for local rpc server access, this allows you to control a user's local Discord client
+    Rpc,
+    /// for local rpc server access, this allows you to update a user's activity
+    RpcActivitiesWrite,
+    /// for local rpc server access, this allows you to receive notifications pushed out to the user
+    RpcNotificationsRead,
+    /// for local rpc server access, this allows you to read a user's screenshare status
+    RpcScreenshareRead,
+    /// for local rpc server access, this allows you to update a user's screenshare settings
+    RpcScreenshareWrite,
+    /// for local rpc server access, this allows you to read a user's video status
+    RpcVideoRead,
+    /// for local rpc server access, this allows you to update a user's video settings
+    RpcVideoWrite,
+    /// for local rpc server access, this allows you to read a user's voice settings and listen for voice events
+    RpcVoiceRead,
+    /// for local rpc server access, this allows you to update a user's voice settings
+    RpcVoiceWrite,
+    /// allows your app to connect to voice on the user's behalf and see all the voice members
+    Voice,
+    /// this generates a webhook that is returned in the oauth token response for authorization code grants
+    WebhookIncoming,
+    /// A scope that is not documented
+    Other(String),
+}
+
+impl Scope {
+    /// Returns the name of the scope as sent to Discord
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::ActivitiesRead => "activities.read",
+            Self::ActivitiesWrite => "activities.write",
+            Self::ApplicationsCommands => "applications.commands",
+            Self::Bot => "bot",
+            Self::Connections => "connections",
+            Self::DmChannelsRead => "dm_channels.read",
+            Self::Email => "email",
+            Self::GdmJoin => "gdm.join",
+            Self::Guilds => "guilds",
+            Self::GuildsJoin => "guilds.join",
+            Self::GuildsMembersRead => "guilds.members.read",
+            Self::Identify => "identify",
+            Self::MessagesRead => "messages.read",
+            Self::RelationshipsRead => "relationships.read",
+            Self::Rpc => "rpc",
+            Self::RpcActivitiesWrite => "rpc.activities.write",
+            Self::RpcNotificationsRead => "rpc.notifications.read",
+            Self::RpcScreenshareRead => "rpc.screenshare.read",
+            Self::RpcScreenshareWrite => "rpc.screenshare.write",
+            Self::RpcVideoRead => "rpc.video.read",
+            Self::RpcVideoWrite => "rpc.video.write",
+            Self::RpcVoiceRead => "rpc.voice.read",
+            Self::RpcVoiceWrite => "rpc.voice.write",
+            Self::Voice => "voice",
+            Self::WebhookIncoming => "webhook.incoming",
+            Self::Other(scope) => scope,
+        }
+    }
+}
+
+impl From<&str> for Scope {
+    fn from(scope: &str) -> Self {
+        match scope {
+            "activities.read" => Self::ActivitiesRead,
+            "activities.write" => Self::ActivitiesWrite,
+            "applications.commands" => Self::ApplicationsCommands,
+            "bot" => Self::Bot,
+            "connections" => Self::Connections,
+            "dm_channels.read" => Self::DmChannelsRead,
+            "email" => Self::Email,
+            "gdm.join" => Self::GdmJoin,
+            "guilds" => Self::Guilds,
+            "guilds.join" => Self::GuildsJoin,
+            "guilds.members.read" => Self::GuildsMembersRead,
+            "identify" => Self::Identify,
+            "messages.read" => Self::MessagesRead,
+            "relationships.read" => Self::RelationshipsRead,
+            "rpc" => Self::Rpc,
+            "rpc.activities.write" => Self::RpcActivitiesWrite,
+            "rpc.notifications.read" => Self::RpcNotificationsRead,
+            "rpc.screenshare.read" => Self::RpcScreenshareRead,
+            "rpc.screenshare.write" => Self::RpcScreenshareWrite,
+            "rpc.video.read" => Self::RpcVideoRead,
+            "rpc.video.write" => Self::RpcVideoWrite,
+            "rpc.voice.read" => Self::RpcVoiceRead,
+            "rpc.voice.write" => Self::RpcVoiceWrite,
+            "voice" => Self::Voice,
+            "webhook.incoming" => Self::WebhookIncoming,
+            scope => Self::Other(scope.to_owned()),
+        }
+    }
+}
+
+impl From<String> for Scope {
+    fn from(scope: String) -> Self {
+        match Self::from(scope.as_str()) {
+            Self::Other(_) => Self::Other(scope),
+            known => known,
+        }
+    }
+}
+
+impl From<Scope> for String {
+    fn from(scope: Scope) -> Self {
+        match scope {
+            Scope::Other(scope) => scope,
+            known => known.as_str().to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VoicePan {
     /// integer
     pub left: u8,
@@ -11,7 +11,7 @@ pub struct VoicePan {
 
 /// <https://discord.com/developers/docs/topics/rpc#setuservoicesettings-set-user-voice-settings-argument-and-response-structure>
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UserVoiceSettings {
     /// string - user id
     pub user_id: String,
@@ -32,7 +32,7 @@ pub struct VoiceAvailableDevice {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#getvoicesettings-voice-settings-input-object>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VoiceSettingsInput {
     /// string - device id
     pub device_id: String,
@@ -43,7 +43,7 @@ pub struct VoiceSettingsInput {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#getvoicesettings-voice-settings-output-object>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VoiceSettingsOutput {
     /// string - device id
     pub device_id: String,
@@ -54,7 +54,7 @@ pub struct VoiceSettingsOutput {
 }
 
 /// <https://discord.com/developers/docs/topics/rpc#getvoicesettings-voice-settings-mode-object>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VoiceSettingsMode {
     /// string - voice setting mode type (can be PUSH_TO_TALK or VOICE_ACTIVITY)
     #[serde(rename = "type")]
@@ -69,7 +69,7 @@ pub struct VoiceSettingsMode {
 
 /// <https://discord.com/developers/docs/topics/rpc#getvoicesettings-get-voice-settings-response-structure>
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct VoiceSettings {
     /// voice settings input object - input settings
     pub input: Option<VoiceSettingsInput>,
@@ -93,7 +93,7 @@ pub struct VoiceSettings {
     pub mute: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SoundboardSound {
     /// string - sound name
     pub name: String,
@@ -0,0 +1,580 @@
+//! Helpers for the OAuth2 authorization flow needed to authenticate with the RPC server
+
+use crate::ipc::{DiscordIpcClient, Shared};
+use crate::models::receive::commands::{AuthenticateData, ReturnedCommand};
+use crate::models::send::commands::{AuthenticateArgs, AuthorizeArgs, SentCommand};
+use crate::models::shared::Scope;
+use crate::transport::{BoxFuture, race};
+use crate::{DiscordRPCError, Result};
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// The URL of Discord's OAuth2 token endpoint
+pub const DISCORD_TOKEN_URL: &str = "https://discord.com/api/v10/oauth2/token";
+
+/// How long before the access token expires to refresh it, unless configured otherwise
+pub const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// How long to wait before trying again after failing to refresh the access token
+const REFRESH_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// A request to exchange a grant for an access token at the OAuth2 token endpoint
+#[derive(Debug, Clone)]
+pub struct TokenRequest {
+    /// The ID of the application
+    pub client_id: String,
+    /// The secret of the application, or None for public clients, which use PKCE instead
+    pub client_secret: Option<String>,
+    /// The grant that is exchanged for an access token
+    pub grant: TokenGrant,
+}
+
+/// A grant that can be exchanged for an access token
+#[derive(Debug, Clone)]
+pub enum TokenGrant {
+    /// An authorization code returned by the AUTHORIZE command, along with the PKCE code verifier if the
+    /// authorization was requested with a code challenge
+    AuthorizationCode {
+        code: String,
+        redirect_uri: String,
+        code_verifier: Option<String>,
+    },
+    /// A refresh token returned along with an earlier access token
+    RefreshToken { refresh_token: String },
+}
+
+impl TokenRequest {
+    /// The form fields sent to the token endpoint
+    pub fn form(&self) -> Vec<(&'static str, String)> {
+        let mut form = vec![("client_id", self.client_id.clone())];
+        if let Some(client_secret) = &self.client_secret {
+            form.push(("client_secret", client_secret.clone()));
+        }
+        match &self.grant {
+            TokenGrant::AuthorizationCode {
+                code,
+                redirect_uri,
+                code_verifier,
+            } => {
+                form.push(("grant_type", "authorization_code".to_owned()));
+                form.push(("code", code.clone()));
+                form.push(("redirect_uri", redirect_uri.clone()));
+                if let Some(code_verifier) = code_verifier {
+                    form.push(("code_verifier", code_verifier.clone()));
+                }
+            }
+            TokenGrant::RefreshToken { refresh_token } => {
+                form.push(("grant_type", "refresh_token".to_owned()));
+                form.push(("refresh_token", refresh_token.clone()));
+            }
+        }
+        form
+    }
+}
+
+/// A PKCE code verifier and the code challenge derived from it, which let public clients obtain an access
+/// token without a client secret
+///
+/// The code challenge is sent with AUTHORIZE and the code verifier with the token request, proving that both
+/// come from the same client. <https://datatracker.ietf.org/doc/html/rfc7636>
+#[derive(Debug, Clone)]
+pub struct Pkce {
+    verifier: String,
+    challenge: String,
+}
+
+impl Pkce {
+    /// The method used to derive the code challenge from the code verifier
+    pub const METHOD: &str = "S256";
+
+    /// Generate a random code verifier
+    pub fn new() -> Self {
+        // Each UUID carries 122 random bits, and hex digits are valid code verifier characters
+        let verifier = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        Self::derive(verifier)
+    }
+
+    /// Use an existing code verifier, which must be between 43 and 128 characters long and only contain
+    /// letters, digits, `-`, `.`, `_` and `~`, otherwise [`DiscordRPCError::InvalidCodeVerifier`] is returned
+    pub fn from_verifier(verifier: impl Into<String>) -> Result<Self> {
+        let verifier = verifier.into();
+        let valid_characters = verifier
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~'));
+        if !(43..=128).contains(&verifier.len()) || !valid_characters {
+            return Err(DiscordRPCError::InvalidCodeVerifier(verifier));
+        }
+        Ok(Self::derive(verifier))
+    }
+
+    fn derive(verifier: String) -> Self {
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self {
+            verifier,
+            challenge,
+        }
+    }
+
+    /// The code verifier, sent with the token request
+    pub fn verifier(&self) -> &str {
+        &self.verifier
+    }
+
+    /// The code challenge, sent with AUTHORIZE
+    pub fn challenge(&self) -> &str {
+        &self.challenge
+    }
+}
+
+impl Default for Pkce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// <https://discord.com/developers/docs/topics/oauth2#authorization-code-grant-access-token-response>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenResponse {
+    /// string - the access token, passed to AUTHENTICATE
+    pub access_token: String,
+    /// string - the type of the access token, which is always Bearer
+    pub token_type: String,
+    /// integer - number of seconds until the access token expires
+    pub expires_in: u64,
+    /// string - token used to obtain a new access token once it expires
+    pub refresh_token: Option<String>,
+    /// string - space-separated list of the authorized scopes
+    pub scope: String,
+}
+
+/// An access token along with what is needed to refresh it, as saved by a [`TokenStore`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StoredToken {
+    /// The access token, passed to AUTHENTICATE
+    pub access_token: String,
+    /// The token used to obtain a new access token once this one expires
+    pub refresh_token: Option<String>,
+    /// When the access token expires, in seconds since the Unix epoch
+    pub expires_at: u64,
+    /// The authorized scopes
+    pub scopes: Vec<Scope>,
+}
+
+impl StoredToken {
+    /// How long until the access token expires, which is zero once it has expired
+    pub fn expires_in(&self) -> Duration {
+        Duration::from_secs(self.expires_at.saturating_sub(unix_time()))
+    }
+}
+
+impl From<TokenResponse> for StoredToken {
+    fn from(response: TokenResponse) -> Self {
+        Self {
+            access_token: response.access_token,
+            refresh_token: response.refresh_token,
+            expires_at: unix_time() + response.expires_in,
+            scopes: response.scope.split_whitespace().map(Scope::from).collect(),
+        }
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Saves the token obtained by a client, see [`ClientBuilder::token_store`](crate::ClientBuilder::token_store)
+pub trait TokenStore: Send + Sync + 'static {
+    /// Returns the saved token, or None if none has been saved
+    fn load(&self) -> Result<Option<StoredToken>>;
+
+    /// Save the token, replacing the one saved before
+    fn save(&self, token: &StoredToken) -> Result<()>;
+
+    /// Remove the saved token
+    fn clear(&self) -> Result<()>;
+}
+
+/// Saves the token as JSON in a file that only the current user can read
+#[derive(Debug, Clone)]
+pub struct JsonFileTokenStore {
+    path: PathBuf,
+}
+
+impl JsonFileTokenStore {
+    /// Save the token in the file at this path
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Save the token of the application in `discord-ipc-rust/<client_id>.json` under the user's
+    /// configuration directory, returning None if the platform has no such directory
+    pub fn for_application(client_id: &str) -> Option<Self> {
+        let directory = dirs::config_dir()?.join("discord-ipc-rust");
+        Some(Self::new(directory.join(format!("{}.json", client_id))))
+    }
+
+    /// The path of the file the token is saved in
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl TokenStore for JsonFileTokenStore {
+    fn load(&self) -> Result<Option<StoredToken>> {
+        match fs::read(&self.path) {
+            Ok(contents) => Ok(Some(serde_json::from_slice(&contents)?)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn save(&self, token: &StoredToken) -> Result<()> {
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        // Write to a temporary file first so that a crash never leaves a partially written token behind
+        let temporary = self.path.with_extension("json.tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(target_family = "unix")]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options.open(&temporary)?;
+        serde_json::to_writer_pretty(file, token)?;
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Exchanges grants for access tokens, which is done over HTTP by [`HttpTokenExchange`]
+///
+/// Implement this to obtain tokens through a backend that holds the client secret, or to stand in for the
+/// token endpoint in tests
+pub trait TokenExchange: Send + Sync + 'static {
+    /// Exchange the grant in the request for an access token
+    fn exchange<'a>(&'a self, request: &'a TokenRequest) -> BoxFuture<'a, Result<TokenResponse>>;
+}
+
+/// Exchanges grants at an OAuth2 token endpoint over HTTP, which requires a tokio runtime
+///
+/// On other runtimes, every exchange fails with [`DiscordRPCError::TokenExchange`], so select another
+/// [`TokenExchange`] with [`ClientBuilder::token_exchange`](crate::ClientBuilder::token_exchange)
+#[derive(Debug, Clone)]
+pub struct HttpTokenExchange {
+    /// Created on first use, since loading the TLS configuration is slow
+    client: OnceLock<reqwest::Client>,
+    url: String,
+}
+
+impl Default for HttpTokenExchange {
+    fn default() -> Self {
+        Self::new(DISCORD_TOKEN_URL)
+    }
+}
+
+impl HttpTokenExchange {
+    /// Exchange grants at the token endpoint with this URL instead of [`DISCORD_TOKEN_URL`]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: OnceLock::new(),
+            url: url.into(),
+        }
+    }
+}
+
+/// The error response of the token endpoint
+#[derive(Deserialize)]
+struct TokenError {
+    error: String,
+    error_description: Option<String>,
+}
+
+impl TokenExchange for HttpTokenExchange {
+    fn exchange<'a>(&'a self, request: &'a TokenRequest) -> BoxFuture<'a, Result<TokenResponse>> {
+        Box::pin(async move {
+            // reqwest panics when it is used without a tokio runtime
+            if tokio::runtime::Handle::try_current().is_err() {
+                return Err(DiscordRPCError::TokenExchange(
+                    "the HTTP token exchange needs a tokio runtime".to_owned(),
+                ));
+            }
+            let response = self
+                .client
+                .get_or_init(reqwest::Client::new)
+                .post(&self.url)
+                .form(&request.form())
+                .send()
+                .await
+                .map_err(|error| DiscordRPCError::TokenExchange(error.to_string()))?;
+            let body = response
+                .bytes()
+                .await
+                .map_err(|error| DiscordRPCError::TokenExchange(error.to_string()))?;
+
+            if let Ok(token) = serde_json::from_slice::<TokenResponse>(&body) {
+                return Ok(token);
+            }
+            let message = match serde_json::from_slice::<TokenError>(&body) {
+                Ok(TokenError {
+                    error,
+                    error_description: Some(description),
+                }) => format!("{}: {}", error, description),
+                Ok(TokenError { error, .. }) => error,
+                Err(_) => format!(
+                    "unexpected response {}",
+                    String::from_utf8_lossy(&body).trim()
+                ),
+            };
+            Err(DiscordRPCError::TokenExchange(message))
+        })
+    }
+}
+
+/// The OAuth2 state of a client
+pub(crate) struct OAuth {
+    exchange: Arc<dyn TokenExchange>,
+    store: Option<Arc<dyn TokenStore>>,
+    refresh_margin: Duration,
+    session: Mutex<OAuthSession>,
+}
+
+/// The token the client is authenticated with and what is needed to refresh it
+#[derive(Default)]
+struct OAuthSession {
+    client_secret: Option<String>,
+    token: Option<StoredToken>,
+    /// Incremented whenever a new token is obtained, which stops the refresh task of the previous one
+    generation: u64,
+}
+
+impl OAuth {
+    pub(crate) fn new(
+        exchange: Arc<dyn TokenExchange>,
+        store: Option<Arc<dyn TokenStore>>,
+        refresh_margin: Duration,
+    ) -> Self {
+        Self {
+            exchange,
+            store,
+            refresh_margin,
+            session: Mutex::default(),
+        }
+    }
+
+    fn save(&self, token: &StoredToken) -> Result<()> {
+        match &self.store {
+            Some(store) => store.save(token),
+            None => Ok(()),
+        }
+    }
+}
+
+impl DiscordIpcClient {
+    /// Request authorization from the user, exchange the authorization code for an access token with the
+    /// client's [`TokenExchange`] and authenticate with it
+    ///
+    /// Without a client secret, the authorization is secured with [`Pkce`] instead, which requires the application
+    /// to be set up as a public client. The redirect URI must be one of those set up for the application in the
+    /// Discord Developer Portal. The token is saved in the client's [`TokenStore`] and refreshed before it expires
+    /// from then on. Returns the authentication data along with the token, which can be used to authenticate again later
+    ///
+    /// If the token cannot be saved, the error of the store is returned, though the client stays authenticated
+    pub async fn authorize_and_authenticate(
+        &self,
+        scopes: Vec<Scope>,
+        client_secret: Option<&str>,
+        redirect_uri: &str,
+    ) -> Result<(AuthenticateData, TokenResponse)> {
+        let pkce = client_secret.is_none().then(Pkce::new);
+        let code = self
+            .authorize(AuthorizeArgs {
+                scopes,
+                client_id: self.client_id.clone(),
+                rpc_token: None,
+                username: None,
+                code_challenge: pkce.as_ref().map(|pkce| pkce.challenge().to_owned()),
+                code_challenge_method: pkce.as_ref().map(|_| Pkce::METHOD.to_owned()),
+            })
+            .await?;
+
+        let request = TokenRequest {
+            client_id: self.client_id.clone(),
+            client_secret: client_secret.map(str::to_owned),
+            grant: TokenGrant::AuthorizationCode {
+                code,
+                redirect_uri: redirect_uri.to_owned(),
+                code_verifier: pkce.map(|pkce| pkce.verifier),
+            },
+        };
+        let token = self.shared.oauth.exchange.exchange(&request).await?;
+
+        // Keep the token before authenticating, so that it is not lost if authenticating fails
+        let saved = keep_fresh(&self.shared, client_secret, token.clone().into());
+        let data = self.authenticate(token.access_token.clone()).await?;
+        saved?;
+        Ok((data, token))
+    }
+
+    /// Authenticate with the token saved in the client's [`TokenStore`], refreshing it first if it expires soon
+    ///
+    /// Public clients pass no client secret. The token is refreshed before it expires from then on. Returns None if
+    /// there is no usable token, in which case the user has to authorize the application again
+    pub async fn authenticate_with_stored_token(
+        &self,
+        client_secret: Option<&str>,
+    ) -> Result<Option<AuthenticateData>> {
+        let oauth = &self.shared.oauth;
+        let Some(mut token) = oauth
+            .store
+            .as_ref()
+            .map(|store| store.load())
+            .transpose()?
+            .flatten()
+        else {
+            return Ok(None);
+        };
+
+        let mut saved = None;
+        if token.expires_in() <= oauth.refresh_margin {
+            let Some(refresh_token) = token.refresh_token.clone() else {
+                return Ok(None);
+            };
+            token = exchange_refresh_token(oauth, &self.client_id, client_secret, refresh_token)
+                .await?;
+            // Keep the new token before authenticating, since the refresh token it replaces is no longer valid
+            saved = Some(keep_fresh(&self.shared, client_secret, token.clone()));
+        }
+
+        let data = self.authenticate(token.access_token.clone()).await?;
+        saved.unwrap_or_else(|| keep_fresh(&self.shared, client_secret, token))?;
+        Ok(Some(data))
+    }
+
+    /// Refresh the access token now and authenticate with the new one, returning it
+    ///
+    /// Fails with the error of the client's [`TokenStore`] if the new token cannot be saved
+    pub async fn refresh_access_token(&self) -> Result<StoredToken> {
+        refresh(&self.shared).await
+    }
+}
+
+/// Saves the token, remembers it and refreshes it before it expires from now on, returning the error of saving it
+fn keep_fresh(shared: &Arc<Shared>, client_secret: Option<&str>, token: StoredToken) -> Result<()> {
+    let saved = shared.oauth.save(&token);
+    let generation = {
+        let mut session = shared.oauth.session.lock().unwrap();
+        session.client_secret = client_secret.map(str::to_owned);
+        session.token = Some(token);
+        session.generation += 1;
+        session.generation
+    };
+
+    let refreshing = shared.clone();
+    shared.transport.spawn(Box::pin(race(
+        refresh_loop(refreshing, generation),
+        shared.stopping(),
+    )));
+    saved
+}
+
+/// Refreshes the token whenever it is about to expire, until a new token is obtained
+async fn refresh_loop(shared: Arc<Shared>, generation: u64) {
+    let margin = shared.oauth.refresh_margin;
+    loop {
+        let expires_in = {
+            let session = shared.oauth.session.lock().unwrap();
+            match &session.token {
+                Some(token)
+                    if session.generation == generation && token.refresh_token.is_some() =>
+                {
+                    token.expires_in()
+                }
+                _ => return,
+            }
+        };
+
+        if expires_in > margin {
+            shared.transport.sleep(expires_in - margin).await;
+        } else if let Err(error) = refresh(&shared).await {
+            eprintln!("Failed to refresh the access token: {}", error);
+            shared.transport.sleep(REFRESH_RETRY_DELAY).await;
+        }
+    }
+}
+
+/// Exchanges the refresh token for a new access token, saves it and authenticates the connection with it
+async fn refresh(shared: &Shared) -> Result<StoredToken> {
+    let (client_secret, refresh_token) = {
+        let session = shared.oauth.session.lock().unwrap();
+        let refresh_token = session
+            .token
+            .as_ref()
+            .and_then(|token| token.refresh_token.clone());
+        (session.client_secret.clone(), refresh_token)
+    };
+    let Some(refresh_token) = refresh_token else {
+        return Err(DiscordRPCError::TokenExchange(
+            "there is no refresh token".to_owned(),
+        ));
+    };
+
+    let token = exchange_refresh_token(
+        &shared.oauth,
+        &shared.client_id,
+        client_secret.as_deref(),
+        refresh_token,
+    )
+    .await?;
+    shared.oauth.session.lock().unwrap().token = Some(token.clone());
+    // Save the new token before authenticating, since the refresh token it replaces is no longer valid
+    let saved = shared.oauth.save(&token);
+
+    // Remember the new token first so that it is used after reconnecting even if the connection is down now
+    let command = SentCommand::Authenticate(AuthenticateArgs {
+        access_token: token.access_token.clone(),
+    });
+    shared.remember(&command);
+    match shared.request(command).await? {
+        ReturnedCommand::Authenticate(_) => {}
+        _ => return Err(DiscordRPCError::UnexpectedResponse),
+    }
+    saved?;
+    Ok(token)
+}
+
+async fn exchange_refresh_token(
+    oauth: &OAuth,
+    client_id: &str,
+    client_secret: Option<&str>,
+    refresh_token: String,
+) -> Result<StoredToken> {
+    let request = TokenRequest {
+        client_id: client_id.to_owned(),
+        client_secret: client_secret.map(str::to_owned),
+        grant: TokenGrant::RefreshToken {
+            refresh_token: refresh_token.clone(),
+        },
+    };
+    let mut token = StoredToken::from(oauth.exchange.exchange(&request).await?);
+    token.refresh_token.get_or_insert(refresh_token);
+    Ok(token)
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+/// Controls how the client reconnects after the connection to the RPC server is lost
+///
+/// Reconnection attempts are delayed with exponential backoff, starting at `initial_delay` and
+/// multiplying by `multiplier` after every failed attempt, up to `max_delay`
+#[derive(Debug, Clone)]
+pub struct ReconnectConfig {
+    /// Delay before the first reconnection attempt
+    pub initial_delay: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_delay: Duration,
+    /// Factor the delay is multiplied by after every failed attempt
+    pub multiplier: f64,
+    /// Number of attempts after which the client gives up, or None to retry forever
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2.0,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    /// Returns the delay before the given attempt, counting from 1
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        // A delay that does not fit in a Duration can only be reached without a cap below Duration::MAX
+        Duration::try_from_secs_f64(delay.min(self.max_delay.as_secs_f64()).max(0.0))
+            .unwrap_or(self.max_delay)
+    }
+
+    /// Whether the given attempt, counting from 1, may be made
+    pub(crate) fn allows_attempt(&self, attempt: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempt <= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_grows_exponentially_up_to_the_cap() {
+        let config = ReconnectConfig::default();
+        assert_eq!(config.delay_for(1), Duration::from_secs(1));
+        assert_eq!(config.delay_for(3), Duration::from_secs(4));
+        assert_eq!(config.delay_for(100), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn delay_without_a_cap_saturates() {
+        let config = ReconnectConfig {
+            max_delay: Duration::MAX,
+            ..ReconnectConfig::default()
+        };
+        assert_eq!(config.delay_for(40), Duration::from_secs(1 << 39));
+        assert_eq!(config.delay_for(100), Duration::MAX);
+        assert_eq!(config.delay_for(u32::MAX), Duration::MAX);
+    }
+}
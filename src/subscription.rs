@@ -0,0 +1,133 @@
+use crate::event_stream::EventStream;
+use crate::ipc::Shared;
+use crate::models::receive::{
+    ReceivedItem,
+    events::{
+        ActivityJoinData, ActivityJoinRequestData, ActivitySpectateData, ChannelCreateData,
+        GuildCreateData, GuildStatusData, MessageData, NotificationCreateData, ReturnedEvent,
+        ScreenshareStateData, SpeakingData, VideoStateData, VoiceChannelSelectData,
+        VoiceConnectionStatusData, VoiceStateData,
+    },
+};
+use crate::models::send::events::SubscribeableEvent;
+use crate::models::shared::voice::VoiceSettings;
+use crate::{DiscordRPCError, Result};
+
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tokio_stream::Stream;
+
+/// The payload carried by one or more [`ReturnedEvent`] variants
+pub trait EventPayload: Sized {
+    /// Extracts the payload from the event, returning None if the event carries a different payload
+    fn from_event(event: ReturnedEvent) -> Option<Self>;
+}
+
+impl EventPayload for ReturnedEvent {
+    fn from_event(event: ReturnedEvent) -> Option<Self> {
+        Some(event)
+    }
+}
+
+macro_rules! impl_event_payload {
+    ($($payload:ty => $($variant:ident)|+;)*) => {
+        $(
+            impl EventPayload for $payload {
+                fn from_event(event: ReturnedEvent) -> Option<Self> {
+                    match event {
+                        $(ReturnedEvent::$variant(data))|+ => Some(data),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_event_payload! {
+    GuildStatusData => GuildStatus;
+    GuildCreateData => GuildCreate;
+    ChannelCreateData => ChannelCreate;
+    VoiceChannelSelectData => VoiceChannelSelect;
+    VoiceSettings => VoiceSettingsUpdate;
+    VoiceStateData => VoiceStateCreate | VoiceStateUpdate | VoiceStateDelete;
+    VoiceConnectionStatusData => VoiceConnectionStatus;
+    MessageData => MessageCreate | MessageUpdate | MessageDelete;
+    SpeakingData => SpeakingStart | SpeakingStop;
+    NotificationCreateData => NotificationCreate;
+    ActivityJoinData => ActivityJoin;
+    ActivitySpectateData => ActivitySpectate;
+    ActivityJoinRequestData => ActivityJoinRequest;
+    VideoStateData => VideoStateUpdate;
+    ScreenshareStateData => ScreenshareStateUpdate;
+}
+
+/// A handle to a subscription created with [`DiscordIpcClient::subscribe`](crate::DiscordIpcClient::subscribe)
+/// that yields the payloads of the subscribed event
+///
+/// Handles for the same event share a single subscription on the RPC server, which is unsubscribed once
+/// the last of them is dropped. Each handle only yields the events dispatched for its own arguments, see
+/// [`SubscribeableEvent::matches`]. A handle that falls behind yields [`DiscordRPCError::Lagged`] in place of the
+/// payloads it skipped, since some of them may have been meant for it. The stream ends once the socket closes
+pub struct Subscription<T> {
+    event: SubscribeableEvent,
+    events: EventStream,
+    shared: Arc<Shared>,
+    payload: PhantomData<fn() -> T>,
+}
+
+impl<T: EventPayload> Subscription<T> {
+    pub(crate) fn new(event: SubscribeableEvent, events: EventStream, shared: Arc<Shared>) -> Self {
+        Self {
+            event,
+            events,
+            shared,
+            payload: PhantomData,
+        }
+    }
+
+    /// The event this handle is subscribed to
+    pub fn event(&self) -> &SubscribeableEvent {
+        &self.event
+    }
+
+    /// Wait for the next payload, returning None once the socket has closed
+    pub async fn recv(&mut self) -> Option<Result<T>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl<T: EventPayload> Stream for Subscription<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<T>>> {
+        loop {
+            let item = match Pin::new(&mut self.events).poll_next(cx) {
+                Poll::Ready(Some(item)) => item,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+            match item {
+                ReceivedItem::Event(event) if self.event.matches(&event) => {
+                    if let Some(payload) = T::from_event(*event) {
+                        return Poll::Ready(Some(Ok(payload)));
+                    }
+                }
+                ReceivedItem::Lagged(skipped) => {
+                    return Poll::Ready(Some(Err(DiscordRPCError::Lagged(skipped))));
+                }
+                ReceivedItem::SocketClosed => return Poll::Ready(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.shared.release_subscription(&self.event);
+    }
+}
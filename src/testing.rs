@@ -0,0 +1,395 @@
+//! An in-process stand-in for the Discord client's RPC server, for testing applications without Discord
+
+use crate::frame::{FrameCodec, HEADER_SIZE, OpCode};
+use crate::models::receive::{
+    codes::{RpcCloseCode, RpcErrorCode},
+    events::ReturnedEvent,
+};
+use crate::models::shared::User;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Value, json};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{UnixListener, UnixStream},
+    sync::mpsc,
+    task::JoinHandle,
+};
+use uuid::Uuid;
+
+/// The reply sent by a [`MockDiscordServer`] to a command
+#[derive(Debug, Clone)]
+pub enum MockReply {
+    /// A successful response carrying the given data
+    Data(Value),
+    /// An ERROR response with the given code and message
+    Error { code: RpcErrorCode, message: String },
+    /// No response at all
+    None,
+}
+
+type CommandHandler = Arc<dyn Fn(&Value) -> MockReply + Send + Sync>;
+
+/// A frame queued for a connection's writer
+enum Outgoing {
+    Frame(OpCode, String),
+    Bytes(Vec<u8>),
+    Disconnect,
+}
+
+struct MockState {
+    user: Mutex<User>,
+    handlers: Mutex<HashMap<String, CommandHandler>>,
+    frames: Mutex<Vec<(OpCode, String)>>,
+    connections: Mutex<Vec<mpsc::UnboundedSender<Outgoing>>>,
+}
+
+/// A mock Discord RPC server listening on a Unix socket
+///
+/// The server binds a `discord-ipc-0` socket in a fresh directory of its own, so that other applications on the
+/// machine never find it, answers the handshake with READY and replies to AUTHORIZE, AUTHENTICATE, SUBSCRIBE,
+/// UNSUBSCRIBE and SET_ACTIVITY. Any other command is answered with an [`RpcErrorCode::InvalidCommand`] error
+/// unless a reply is scripted with [`MockDiscordServer::on_command`]. The socket and its directory are removed
+/// when the server is dropped
+///
+/// Connect to it with [`ClientBuilder::pipe_path`](crate::ClientBuilder::pipe_path) and [`MockDiscordServer::path`]
+pub struct MockDiscordServer {
+    path: PathBuf,
+    /// The directory created for the socket, which is removed along with it
+    directory: Option<PathBuf>,
+    state: Arc<MockState>,
+    accept_task: JoinHandle<()>,
+}
+
+impl MockDiscordServer {
+    /// Bind the socket in a fresh temporary directory and start accepting connections
+    pub async fn start() -> io::Result<Self> {
+        let directory =
+            std::env::temp_dir().join(format!("discord-ipc-rust-mock-{}", Uuid::new_v4().simple()));
+        std::fs::create_dir(&directory)?;
+        match Self::listen(&directory).await {
+            Ok(mut server) => {
+                server.directory = Some(directory);
+                Ok(server)
+            }
+            Err(error) => {
+                let _ = std::fs::remove_dir(&directory);
+                Err(error)
+            }
+        }
+    }
+
+    /// Bind the first free `discord-ipc-N` socket in the directory and start accepting connections
+    ///
+    /// Sockets left behind by servers that were never dropped, such as those of a killed test process, are
+    /// removed and reused. Note that Discord clients search some directories, such as `/tmp`, for sockets, so
+    /// applications on the machine may connect to a server in one of them
+    pub async fn start_in(directory: impl AsRef<Path>) -> io::Result<Self> {
+        Self::listen(directory.as_ref()).await
+    }
+
+    async fn listen(directory: &Path) -> io::Result<Self> {
+        let (path, listener) = bind_free_socket(directory)?;
+        let state = Arc::new(MockState {
+            user: Mutex::new(User {
+                id: "1045800378228281345".to_owned(),
+                username: "mock_user".to_owned(),
+                avatar: None,
+                bot: Some(false),
+                flags: Some(0),
+                premium_type: Some(0),
+            }),
+            handlers: Mutex::default(),
+            frames: Mutex::default(),
+            connections: Mutex::default(),
+        });
+        let accept_task = tokio::spawn(accept_loop(listener, state.clone()));
+
+        Ok(Self {
+            path,
+            directory: None,
+            state,
+            accept_task,
+        })
+    }
+
+    /// The path of the socket the server is listening on
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Set the user reported in the READY event of subsequent connections
+    pub fn set_user(&self, user: User) {
+        *self.state.user.lock().unwrap() = user;
+    }
+
+    /// Script the reply to a command, replacing any previous or default reply
+    /// The handler receives the full command payload, including `args` and `nonce`
+    pub fn on_command<F>(&self, cmd: &str, handler: F)
+    where
+        F: Fn(&Value) -> MockReply + Send + Sync + 'static,
+    {
+        self.state
+            .handlers
+            .lock()
+            .unwrap()
+            .insert(cmd.to_owned(), Arc::new(handler));
+    }
+
+    /// Dispatch an event to every connected client
+    pub fn dispatch(&self, event: &ReturnedEvent) {
+        let mut payload = serde_json::to_value(event).expect("events are serializable");
+        payload["cmd"] = json!("DISPATCH");
+        payload["nonce"] = Value::Null;
+        self.send_frame(OpCode::Frame, &payload.to_string());
+    }
+
+    /// Send a raw frame to every connected client
+    pub fn send_frame(&self, opcode: OpCode, payload: &str) {
+        self.broadcast(|| Outgoing::Frame(opcode, payload.to_owned()));
+    }
+
+    /// Send raw bytes to every connected client, such as a malformed frame
+    pub fn send_bytes(&self, bytes: &[u8]) {
+        self.broadcast(|| Outgoing::Bytes(bytes.to_vec()));
+    }
+
+    /// Send a CLOSE frame to every connected client and disconnect them
+    pub fn close(&self, code: RpcCloseCode, message: &str) {
+        let payload = json!({ "code": code, "message": message }).to_string();
+        self.send_frame(OpCode::Close, &payload);
+        self.disconnect();
+    }
+
+    /// Drop every connection without sending a CLOSE frame, as happens when Discord exits
+    pub fn disconnect(&self) {
+        self.broadcast(|| Outgoing::Disconnect);
+        self.state.connections.lock().unwrap().clear();
+    }
+
+    /// Number of clients currently connected
+    pub fn connection_count(&self) -> usize {
+        let mut connections = self.state.connections.lock().unwrap();
+        connections.retain(|connection| !connection.is_closed());
+        connections.len()
+    }
+
+    /// Every frame received from clients so far, in order
+    pub fn received_frames(&self) -> Vec<(OpCode, String)> {
+        self.state.frames.lock().unwrap().clone()
+    }
+
+    /// The payloads of every command received from clients so far, in order
+    pub fn received_commands(&self) -> Vec<Value> {
+        self.received_frames()
+            .into_iter()
+            .filter(|(opcode, _)| *opcode == OpCode::Frame)
+            .filter_map(|(_, payload)| serde_json::from_str(&payload).ok())
+            .collect()
+    }
+
+    fn broadcast(&self, outgoing: impl Fn() -> Outgoing) {
+        for connection in self.state.connections.lock().unwrap().iter() {
+            let _ = connection.send(outgoing());
+        }
+    }
+}
+
+impl Drop for MockDiscordServer {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+        self.disconnect();
+        let _ = std::fs::remove_file(&self.path);
+        if let Some(directory) = &self.directory {
+            let _ = std::fs::remove_dir(directory);
+        }
+    }
+}
+
+fn bind_free_socket(directory: &Path) -> io::Result<(PathBuf, UnixListener)> {
+    for i in 0..10 {
+        let path = directory.join(format!("discord-ipc-{}", i));
+        if path.exists() && !is_stale_socket(&path) {
+            continue;
+        }
+        let _ = std::fs::remove_file(&path);
+        match UnixListener::bind(&path) {
+            Ok(listener) => return Ok((path, listener)),
+            Err(error) if error.kind() == io::ErrorKind::AddrInUse => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AddrInUse,
+        "every discord-ipc socket in the directory is in use",
+    ))
+}
+
+/// Whether the path is a socket that nothing listens on anymore, which is the case when connecting is refused
+fn is_stale_socket(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    let is_socket =
+        std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket());
+    is_socket
+        && std::os::unix::net::UnixStream::connect(path)
+            .is_err_and(|error| error.kind() == io::ErrorKind::ConnectionRefused)
+}
+
+async fn accept_loop(listener: UnixListener, state: Arc<MockState>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        state.connections.lock().unwrap().push(sender.clone());
+        tokio::spawn(serve_connection(stream, state.clone(), sender, receiver));
+    }
+}
+
+async fn serve_connection(
+    stream: UnixStream,
+    state: Arc<MockState>,
+    sender: mpsc::UnboundedSender<Outgoing>,
+    mut receiver: mpsc::UnboundedReceiver<Outgoing>,
+) {
+    let (mut read_half, mut write_half) = stream.into_split();
+
+    let codec = FrameCodec::default();
+
+    let writer = tokio::spawn(async move {
+        loop {
+            let packet = match receiver.recv().await {
+                Some(Outgoing::Frame(opcode, payload)) => {
+                    let mut packet = Vec::new();
+                    if codec
+                        .encode(opcode, payload.as_bytes(), &mut packet)
+                        .is_err()
+                    {
+                        break;
+                    }
+                    packet
+                }
+                Some(Outgoing::Bytes(bytes)) => bytes,
+                Some(Outgoing::Disconnect) | None => break,
+            };
+            if write_half.write_all(&packet).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut client_id = None;
+    loop {
+        let mut header = [0u8; HEADER_SIZE];
+        if read_half.read_exact(&mut header).await.is_err() {
+            break;
+        }
+        let Ok((opcode, length)) = codec.decode_header(header) else {
+            break;
+        };
+        let mut data = vec![0u8; length];
+        if read_half.read_exact(&mut data).await.is_err() {
+            break;
+        }
+        let (Ok(opcode), Ok(payload)) = (OpCode::try_from(opcode), String::from_utf8(data)) else {
+            break;
+        };
+        state.frames.lock().unwrap().push((opcode, payload.clone()));
+
+        let value = serde_json::from_str::<Value>(&payload).unwrap_or(Value::Null);
+        let reply = match opcode {
+            OpCode::Handshake => match value["client_id"].as_str() {
+                Some(id) if !id.is_empty() => {
+                    client_id = Some(id.to_owned());
+                    let user = state.user.lock().unwrap().clone();
+                    Outgoing::Frame(OpCode::Frame, ready_payload(&user).to_string())
+                }
+                _ => {
+                    let payload = json!({ "code": RpcCloseCode::InvalidClientId, "message": "Invalid Client ID" });
+                    let _ = sender.send(Outgoing::Frame(OpCode::Close, payload.to_string()));
+                    break;
+                }
+            },
+            OpCode::Frame => {
+                let cmd = value["cmd"].as_str().unwrap_or_default();
+                let handler = state.handlers.lock().unwrap().get(cmd).cloned();
+                let reply = match handler {
+                    Some(handler) => handler(&value),
+                    None => default_reply(&state, client_id.as_deref(), &value),
+                };
+                match response_payload(&value, reply) {
+                    Some(payload) => Outgoing::Frame(OpCode::Frame, payload.to_string()),
+                    None => continue,
+                }
+            }
+            OpCode::Ping => Outgoing::Frame(OpCode::Pong, payload),
+            OpCode::Close => break,
+            OpCode::Pong => continue,
+        };
+        if sender.send(reply).is_err() {
+            break;
+        }
+    }
+
+    let _ = sender.send(Outgoing::Disconnect);
+    let _ = writer.await;
+}
+
+fn ready_payload(user: &User) -> Value {
+    json!({
+        "cmd": "DISPATCH",
+        "evt": "READY",
+        "data": {
+            "v": 1,
+            "config": {
+                "cdn_host": "cdn.discordapp.com",
+                "api_endpoint": "//discord.com/api",
+                "environment": "production",
+            },
+            "user": user,
+        },
+        "nonce": null,
+    })
+}
+
+fn default_reply(state: &MockState, client_id: Option<&str>, command: &Value) -> MockReply {
+    match command["cmd"].as_str().unwrap_or_default() {
+        "AUTHORIZE" => MockReply::Data(json!({ "code": "mock_authorization_code" })),
+        "AUTHENTICATE" => MockReply::Data(json!({
+            "user": *state.user.lock().unwrap(),
+            "scopes": ["rpc", "identify"],
+            "expires": "2099-01-01T00:00:00.000Z",
+            "application": {
+                "description": "",
+                "icon": null,
+                "id": client_id,
+                "rpc_origins": null,
+                "name": "Mock Application",
+            },
+        })),
+        "SUBSCRIBE" | "UNSUBSCRIBE" => MockReply::Data(json!({ "evt": command["evt"] })),
+        "SET_ACTIVITY" => MockReply::Data(command["args"]["activity"].clone()),
+        cmd => MockReply::Error {
+            code: RpcErrorCode::InvalidCommand,
+            message: format!("Invalid command: {}", cmd),
+        },
+    }
+}
+
+fn response_payload(command: &Value, reply: MockReply) -> Option<Value> {
+    let (evt, data) = match reply {
+        MockReply::Data(data) => (Value::Null, data),
+        MockReply::Error { code, message } => {
+            (json!("ERROR"), json!({ "code": code, "message": message }))
+        }
+        MockReply::None => return None,
+    };
+    Some(json!({
+        "cmd": command["cmd"],
+        "evt": evt,
+        "data": data,
+        "nonce": command["nonce"],
+    }))
+}
@@ -0,0 +1,50 @@
+#[cfg(feature = "async")]
+use crate::{
+    DiscordRPCError, Result,
+    transport::{Transport, race},
+};
+
+use std::fmt;
+#[cfg(feature = "async")]
+use std::{future::Future, time::Duration};
+
+/// The phase of the connection that took too long
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutPhase {
+    /// Opening the socket
+    Connect,
+    /// Sending the handshake and waiting for the READY event
+    Handshake,
+    /// Sending a command and waiting for its response
+    Request,
+}
+
+impl fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connect => write!(f, "connect"),
+            Self::Handshake => write!(f, "handshake"),
+            Self::Request => write!(f, "request"),
+        }
+    }
+}
+
+/// Awaits the future, failing with [`DiscordRPCError::Timeout`] if it takes longer than the duration
+#[cfg(feature = "async")]
+pub(crate) async fn with_timeout<T>(
+    transport: &dyn Transport,
+    duration: Option<Duration>,
+    phase: TimeoutPhase,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    match duration {
+        Some(after) => {
+            let elapsed = async {
+                transport.sleep(after).await;
+                Err(DiscordRPCError::Timeout { phase, after })
+            };
+            race(future, elapsed).await
+        }
+        None => future.await,
+    }
+}
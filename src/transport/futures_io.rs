@@ -0,0 +1,27 @@
+use super::{BoxFuture, TransportReader, TransportWriter};
+
+use std::io::{self, IoSlice};
+
+use futures_lite::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// A half of a connection made with the `futures` I/O traits, which smol and async-std share
+pub(super) struct FuturesIo<T>(pub T);
+
+impl<T: AsyncRead + Unpin + Send> TransportReader for FuturesIo<T> {
+    fn read_exact<'a>(&'a mut self, buf: &'a mut [u8]) -> BoxFuture<'a, io::Result<()>> {
+        Box::pin(self.0.read_exact(buf))
+    }
+}
+
+impl<T: AsyncWrite + Unpin + Send> TransportWriter for FuturesIo<T> {
+    fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> BoxFuture<'a, io::Result<()>> {
+        Box::pin(self.0.write_all(buf))
+    }
+
+    fn write_vectored<'a>(
+        &'a mut self,
+        bufs: &'a [IoSlice<'a>],
+    ) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(self.0.write_vectored(bufs))
+    }
+}
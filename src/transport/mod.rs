@@ -0,0 +1,103 @@
+//! The runtime-specific parts of the async client, so that it can run on tokio, smol or async-std
+
+#[cfg(any(feature = "smol", feature = "async-std"))]
+mod futures_io;
+
+#[cfg(feature = "async-std")]
+mod async_std_transport;
+#[cfg(feature = "smol")]
+mod smol_transport;
+#[cfg(feature = "tokio")]
+mod tokio_transport;
+
+#[cfg(feature = "async-std")]
+pub use async_std_transport::AsyncStdTransport;
+#[cfg(feature = "smol")]
+pub use smol_transport::SmolTransport;
+#[cfg(feature = "tokio")]
+pub use tokio_transport::TokioTransport;
+
+use std::future::Future;
+use std::io::{self, IoSlice};
+use std::path::Path;
+use std::pin::{Pin, pin};
+use std::task::Poll;
+use std::time::Duration;
+
+/// The transport used when none is configured, which is the first enabled out of tokio, smol and async-std
+#[cfg(feature = "tokio")]
+pub type DefaultTransport = TokioTransport;
+/// The transport used when none is configured, which is the first enabled out of tokio, smol and async-std
+#[cfg(all(not(feature = "tokio"), feature = "smol"))]
+pub type DefaultTransport = SmolTransport;
+/// The transport used when none is configured, which is the first enabled out of tokio, smol and async-std
+#[cfg(all(not(feature = "tokio"), not(feature = "smol"), feature = "async-std"))]
+pub type DefaultTransport = AsyncStdTransport;
+
+/// A boxed future that can be sent between threads
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The read and write halves of a connection
+pub type Connection = (Box<dyn TransportReader>, Box<dyn TransportWriter>);
+
+/// Opens connections to the RPC server and runs the client's background tasks on an async runtime
+pub trait Transport: Send + Sync + 'static {
+    /// Connect to the socket (named pipe on Windows) at the path
+    fn connect<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<Connection>>;
+
+    /// Run the task in the background until it completes
+    fn spawn(&self, task: BoxFuture<'static, ()>);
+
+    /// Run the task in the background like [`Transport::spawn`], but return false instead of panicking if it
+    /// cannot be spawned from the current context, such as outside of the runtime
+    ///
+    /// By default, this calls [`Transport::spawn`]
+    fn try_spawn(&self, task: BoxFuture<'static, ()>) -> bool {
+        self.spawn(task);
+        true
+    }
+
+    /// Complete after the duration has elapsed
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// The read half of a connection
+pub trait TransportReader: Send {
+    /// Read exactly enough bytes to fill the buffer
+    fn read_exact<'a>(&'a mut self, buf: &'a mut [u8]) -> BoxFuture<'a, io::Result<()>>;
+}
+
+/// The write half of a connection
+pub trait TransportWriter: Send {
+    /// Write the entire buffer
+    fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> BoxFuture<'a, io::Result<()>>;
+
+    /// Write as much of the buffers as possible with a single write, returning the number of bytes written
+    ///
+    /// By default, this writes the first non-empty buffer in full
+    fn write_vectored<'a>(
+        &'a mut self,
+        bufs: &'a [IoSlice<'a>],
+    ) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(async move {
+            let Some(buf) = bufs.iter().find(|buf| !buf.is_empty()) else {
+                return Ok(0);
+            };
+            self.write_all(buf).await?;
+            Ok(buf.len())
+        })
+    }
+}
+
+/// Polls both futures until either completes, returning its output
+pub(crate) async fn race<T>(first: impl Future<Output = T>, second: impl Future<Output = T>) -> T {
+    let mut first = pin!(first);
+    let mut second = pin!(second);
+    std::future::poll_fn(|cx| {
+        if let Poll::Ready(output) = first.as_mut().poll(cx) {
+            return Poll::Ready(output);
+        }
+        second.as_mut().poll(cx)
+    })
+    .await
+}
@@ -0,0 +1,35 @@
+use super::futures_io::FuturesIo;
+use super::{BoxFuture, Connection, Transport, TransportReader, TransportWriter};
+
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Runs the client on smol, spawning its background tasks on smol's global executor
+///
+/// Only available on Unix, since smol has no named pipes that can be read and written at the same time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SmolTransport;
+
+impl Transport for SmolTransport {
+    fn connect<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<Connection>> {
+        Box::pin(async move {
+            let stream = smol::net::unix::UnixStream::connect(path).await?;
+            let (read_half, write_half) = (stream.clone(), stream);
+
+            let reader: Box<dyn TransportReader> = Box::new(FuturesIo(read_half));
+            let writer: Box<dyn TransportWriter> = Box::new(FuturesIo(write_half));
+            Ok((reader, writer))
+        })
+    }
+
+    fn spawn(&self, task: BoxFuture<'static, ()>) {
+        smol::spawn(task).detach();
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(async move {
+            smol::Timer::after(duration).await;
+        })
+    }
+}
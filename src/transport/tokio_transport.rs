@@ -0,0 +1,70 @@
+use super::{BoxFuture, Connection, Transport, TransportReader, TransportWriter};
+
+use std::io::{self, IoSlice};
+use std::path::Path;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Runs the client on tokio, which must be the runtime the client is created on
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioTransport;
+
+impl Transport for TokioTransport {
+    fn connect<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<Connection>> {
+        Box::pin(async move {
+            #[cfg(target_family = "unix")]
+            let (read_half, write_half) = tokio::net::UnixStream::connect(path).await?.into_split();
+            #[cfg(target_family = "windows")]
+            let (read_half, write_half) =
+                tokio::io::split(tokio::net::windows::named_pipe::ClientOptions::new().open(path)?);
+
+            let reader: Box<dyn TransportReader> = Box::new(TokioIo(read_half));
+            let writer: Box<dyn TransportWriter> = Box::new(TokioIo(write_half));
+            Ok((reader, writer))
+        })
+    }
+
+    fn spawn(&self, task: BoxFuture<'static, ()>) {
+        tokio::spawn(task);
+    }
+
+    fn try_spawn(&self, task: BoxFuture<'static, ()>) -> bool {
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(task);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A half of a connection made with tokio's I/O traits
+struct TokioIo<T>(T);
+
+impl<T: AsyncRead + Unpin + Send> TransportReader for TokioIo<T> {
+    fn read_exact<'a>(&'a mut self, buf: &'a mut [u8]) -> BoxFuture<'a, io::Result<()>> {
+        Box::pin(async move {
+            self.0.read_exact(buf).await?;
+            Ok(())
+        })
+    }
+}
+
+impl<T: AsyncWrite + Unpin + Send> TransportWriter for TokioIo<T> {
+    fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> BoxFuture<'a, io::Result<()>> {
+        Box::pin(self.0.write_all(buf))
+    }
+
+    fn write_vectored<'a>(
+        &'a mut self,
+        bufs: &'a [IoSlice<'a>],
+    ) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(self.0.write_vectored(bufs))
+    }
+}
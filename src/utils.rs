@@ -1,46 +1,68 @@
-use crate::Result;
+use crate::builder::{ConnectOptions, PIPE_PATH_ENV_VAR};
+use crate::discovery::EndpointSource;
+use crate::models::send::commands::SentCommand;
+use crate::models::shared::Scope;
+use crate::{DiscordRPCError, Result};
 
-use std::collections::HashSet;
-use std::convert::TryInto;
 use std::path::{Path, PathBuf};
 
 use serde_json::Value;
 use uuid::Uuid;
 
-pub fn create_packet_json(value: &mut serde_json::Value) -> Result<String> {
+/// Inserts a freshly generated nonce into the payload, returning the nonce and the serialized packet
+pub fn create_packet_json(value: &mut serde_json::Value) -> Result<(String, String)> {
     let uuid = Uuid::new_v4().to_string();
 
     let payload = value.as_object_mut().expect("payload must be an object");
-    payload.insert("nonce".to_string(), Value::String(uuid));
+    payload.insert("nonce".to_string(), Value::String(uuid.clone()));
 
-    Ok(serde_json::to_string(&payload)?)
+    Ok((uuid, serde_json::to_string(&payload)?))
 }
 
-pub fn pack(opcode: u32, data_len: u32) -> Result<Vec<u8>> {
-    let mut bytes = Vec::new();
-
-    for byte_array in &[opcode.to_le_bytes(), data_len.to_le_bytes()] {
-        bytes.extend_from_slice(byte_array);
+/// Fails with [`DiscordRPCError::MissingScope`] if the command needs a scope that is not among the granted scopes
+/// Nothing is checked while the granted scopes are unknown, which is the case before authenticating
+pub fn check_scopes(
+    command: &SentCommand,
+    command_json: &Value,
+    granted: Option<&[Scope]>,
+) -> Result<()> {
+    let Some(granted) = granted else {
+        return Ok(());
+    };
+    let missing = command.missing_scopes(granted);
+    if missing.is_empty() {
+        return Ok(());
     }
-
-    Ok(bytes)
-}
-
-pub fn unpack(data: Vec<u8>) -> Result<(u32, u32)> {
-    let data = data.as_slice();
-    let (opcode, header) = data.split_at(std::mem::size_of::<u32>());
-
-    let opcode = u32::from_le_bytes(opcode.try_into()?);
-    let header = u32::from_le_bytes(header.try_into()?);
-
-    Ok((opcode, header))
+    Err(DiscordRPCError::MissingScope {
+        command: command_json["cmd"].as_str().unwrap_or_default().to_owned(),
+        missing,
+    })
 }
 
 /// Finds the active pipe path of the RPC server
-pub fn get_pipe_path() -> Option<PathBuf> {
-    let possible_paths = get_os_pipe_paths();
+///
+/// An explicit path in the options takes precedence over the path in the [`PIPE_PATH_ENV_VAR`] environment variable,
+/// which takes precedence over searching the extra directories in the options followed by the platform's default locations
+pub fn get_pipe_path(options: &ConnectOptions) -> Option<PathBuf> {
+    if let Some(path) = &options.pipe_path {
+        return Some(path.clone());
+    }
+    if let Some(path) = std::env::var_os(PIPE_PATH_ENV_VAR) {
+        return Some(PathBuf::from(path));
+    }
 
-    for i in 0..10 {
+    let mut possible_paths: Vec<String> = options
+        .search_dirs
+        .iter()
+        .map(|dir| dir.join("discord-ipc-").to_string_lossy().into_owned())
+        .collect();
+    possible_paths.extend(get_os_pipe_paths().into_iter().map(|(p, _)| p));
+
+    let indices = match options.instance {
+        Some(i) => i..=i,
+        None => 0..=9,
+    };
+    for i in indices {
         for p in &possible_paths {
             let path: String = format!("{}{}", p, i);
             let path = Path::new(&path);
@@ -54,32 +76,41 @@ pub fn get_pipe_path() -> Option<PathBuf> {
     None
 }
 
+/// The platform's default socket path prefixes in search order, along with where each comes from
 #[cfg(target_os = "windows")]
-fn get_os_pipe_paths() -> HashSet<String> {
-    let mut possible_paths = HashSet::new();
-    possible_paths.insert(r"\\?\pipe\discord-ipc-".to_owned());
-    possible_paths
+pub(crate) fn get_os_pipe_paths() -> Vec<(String, EndpointSource)> {
+    vec![(
+        r"\\?\pipe\discord-ipc-".to_owned(),
+        EndpointSource::NamedPipe,
+    )]
 }
 
+/// The platform's default socket path prefixes in search order, along with where each comes from
 #[cfg(all(target_family = "unix", not(target_os = "macos")))]
-fn get_os_pipe_paths() -> HashSet<String> {
-    let mut possible_paths = HashSet::new();
-    possible_paths.insert("/tmp/discord-ipc-".to_owned());
+pub(crate) fn get_os_pipe_paths() -> Vec<(String, EndpointSource)> {
+    let mut possible_paths = vec![("/tmp/discord-ipc-".to_owned(), EndpointSource::Tmp)];
 
     if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
-        possible_paths.insert(runtime_dir.clone() + "/app/com.discordapp.Discord/discord-ipc-"); // Discord installed via Flatpak
-        possible_paths.insert(runtime_dir.clone() + "/snap.discord/discord-ipc-"); // Discord installed via Snap
-        possible_paths.insert(runtime_dir + "/discord-ipc-");
+        possible_paths.push((
+            runtime_dir.clone() + "/app/com.discordapp.Discord/discord-ipc-",
+            EndpointSource::Flatpak,
+        ));
+        possible_paths.push((
+            runtime_dir.clone() + "/snap.discord/discord-ipc-",
+            EndpointSource::Snap,
+        ));
+        possible_paths.push((runtime_dir + "/discord-ipc-", EndpointSource::XdgRuntime));
     }
 
     possible_paths
 }
 
+/// The platform's default socket path prefixes in search order, along with where each comes from
 #[cfg(target_os = "macos")]
-fn get_os_pipe_paths() -> HashSet<String> {
-    let mut possible_paths = HashSet::new();
+pub(crate) fn get_os_pipe_paths() -> Vec<(String, EndpointSource)> {
+    let mut possible_paths = Vec::new();
     if let Ok(runtime_dir) = std::env::var("TMPDIR") {
-        possible_paths.insert(runtime_dir + "/discord-ipc-");
+        possible_paths.push((runtime_dir + "/discord-ipc-", EndpointSource::Tmp));
     }
     possible_paths
 }
@@ -0,0 +1,79 @@
+use discord_ipc_rust::blocking::DiscordIpcClient;
+use discord_ipc_rust::models::receive::{
+    ReceivedItem,
+    codes::RpcCloseCode,
+    events::{ReturnedEvent, SpeakingData},
+};
+use discord_ipc_rust::models::send::{ActivityBuilder, events::SubscribeableEvent};
+use discord_ipc_rust::testing::MockDiscordServer;
+use discord_ipc_rust::{ConnectOptions, DiscordRPCError};
+
+use std::sync::Arc;
+use std::time::Duration;
+
+fn options(server: &MockDiscordServer) -> ConnectOptions {
+    ConnectOptions {
+        pipe_path: Some(server.path().to_path_buf()),
+        ..ConnectOptions::default()
+    }
+}
+
+// The mock server runs on the test's runtime, so the blocking client runs on a blocking thread
+#[tokio::test(flavor = "multi_thread")]
+async fn blocking_client_sets_activity() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let options = options(&server);
+
+    let activity = tokio::task::spawn_blocking(move || {
+        let (mut client, user) = DiscordIpcClient::connect("1234".to_owned(), &options).unwrap();
+        assert_eq!(user.username, "mock_user");
+
+        let activity = ActivityBuilder::new().state("Testing").build().unwrap();
+        let activity = client.set_activity(activity).unwrap();
+        client.clear_activity().unwrap();
+        activity
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(activity.unwrap().state.as_deref(), Some("Testing"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocking_client_receives_events() {
+    let server = Arc::new(MockDiscordServer::start().await.unwrap());
+    let options = options(&server);
+
+    tokio::task::spawn_blocking(move || {
+        let (mut client, _) = DiscordIpcClient::connect("1234".to_owned(), &options).unwrap();
+        let event = SubscribeableEvent::SpeakingStart {
+            channel_id: "1".to_owned(),
+        };
+        client.subscribe(event).unwrap();
+        let idle = client.recv_timeout(Duration::from_millis(20)).unwrap();
+        assert!(idle.is_none());
+
+        server.dispatch(&ReturnedEvent::SpeakingStart(SpeakingData {
+            user_id: "42".to_owned(),
+        }));
+        server.close(RpcCloseCode::RateLimited, "Slow down");
+
+        match client.recv().unwrap() {
+            ReceivedItem::Event(event) => {
+                assert!(
+                    matches!(*event, ReturnedEvent::SpeakingStart(data) if data.user_id == "42")
+                )
+            }
+            item => panic!("unexpected item {:?}", item),
+        }
+        assert!(matches!(
+            client.recv(),
+            Err(DiscordRPCError::Closed {
+                code: RpcCloseCode::RateLimited,
+                ..
+            })
+        ));
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,128 @@
+use discord_ipc_rust::models::receive::{
+    ReceivedItem, commands::ReturnedCommand, events::ReturnedEvent,
+};
+use discord_ipc_rust::{DiscordRPCError, Frame, FrameCodec, OpCode};
+
+#[test]
+fn encoded_frame_decodes_into_item() {
+    let codec = FrameCodec::default();
+    let payload =
+        br#"{"cmd":"DISPATCH","evt":"SPEAKING_START","data":{"user_id":"42"},"nonce":null}"#;
+
+    let mut buffer = Vec::new();
+    codec.encode(OpCode::Frame, payload, &mut buffer).unwrap();
+    let frame = codec.decode(&mut buffer).unwrap().unwrap();
+
+    assert!(buffer.is_empty());
+    assert_eq!(frame.opcode, OpCode::Frame);
+    match frame.parse::<ReceivedItem>().unwrap() {
+        ReceivedItem::Event(event) => {
+            assert!(matches!(*event, ReturnedEvent::SpeakingStart(data) if data.user_id == "42"))
+        }
+        item => panic!("unexpected item {:?}", item),
+    }
+}
+
+#[test]
+fn partial_frame_waits_for_more_bytes() {
+    let codec = FrameCodec::default();
+    let mut encoded = Vec::new();
+    codec
+        .encode(OpCode::Ping, br#"{"nonce":"abc"}"#, &mut encoded)
+        .unwrap();
+
+    let mut buffer = encoded[..10].to_vec();
+    assert!(codec.decode(&mut buffer).unwrap().is_none());
+    buffer.extend_from_slice(&encoded[10..]);
+
+    let frame = codec.decode(&mut buffer).unwrap().unwrap();
+    assert_eq!(frame.opcode, OpCode::Ping);
+    assert_eq!(frame.payload, br#"{"nonce":"abc"}"#);
+}
+
+#[test]
+fn frames_following_a_decoded_frame_stay_buffered() {
+    let codec = FrameCodec::default();
+    let mut buffer = Vec::new();
+    codec.encode(OpCode::Ping, b"first", &mut buffer).unwrap();
+    codec.encode(OpCode::Pong, b"second", &mut buffer).unwrap();
+
+    let first = codec.decode(&mut buffer).unwrap().unwrap();
+    let second = codec.decode(&mut buffer).unwrap().unwrap();
+
+    assert_eq!(
+        (first.opcode, first.payload),
+        (OpCode::Ping, b"first".to_vec())
+    );
+    assert_eq!(
+        (second.opcode, second.payload),
+        (OpCode::Pong, b"second".to_vec())
+    );
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn oversized_frame_is_rejected_before_allocating() {
+    let codec = FrameCodec::new(16);
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&1u32.to_le_bytes());
+    buffer.extend_from_slice(&u32::MAX.to_le_bytes());
+
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(DiscordRPCError::FrameTooLarge { max: 16, .. })
+    ));
+    assert!(matches!(
+        codec.encode(OpCode::Frame, &[0; 17], &mut Vec::new()),
+        Err(DiscordRPCError::FrameTooLarge {
+            length: 17,
+            max: 16
+        })
+    ));
+}
+
+#[test]
+fn unknown_opcode_is_consumed() {
+    let codec = FrameCodec::default();
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&9u32.to_le_bytes());
+    buffer.extend_from_slice(&2u32.to_le_bytes());
+    buffer.extend_from_slice(b"{}");
+
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(DiscordRPCError::UnknownOpCode(9))
+    ));
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn parse_item_returns_the_nonce_with_the_item() {
+    let frame = |payload: &str| Frame {
+        opcode: OpCode::Frame,
+        payload: payload.as_bytes().to_vec(),
+    };
+
+    let (nonce, item) = frame(
+        r#"{"cmd":"GET_GUILDS","data":{"code":4000,"message":"Invalid"},"evt":"ERROR","nonce":"1"}"#,
+    )
+    .parse_item();
+    assert_eq!(nonce.as_deref(), Some("1"));
+    assert!(matches!(
+        item.unwrap(),
+        ReceivedItem::Event(event) if matches!(*event, ReturnedEvent::Error(_))
+    ));
+
+    let (nonce, item) =
+        frame(r#"{"cmd":"SET_ACTIVITY","data":null,"evt":null,"nonce":"2"}"#).parse_item();
+    assert_eq!(nonce.as_deref(), Some("2"));
+    assert!(matches!(
+        item.unwrap(),
+        ReceivedItem::Command(command) if matches!(*command, ReturnedCommand::SetActivity(None))
+    ));
+
+    let (nonce, item) =
+        frame(r#"{"cmd":"GET_GUILDS","data":{"guilds":1},"evt":null,"nonce":"3"}"#).parse_item();
+    assert_eq!(nonce.as_deref(), Some("3"));
+    assert!(matches!(item, Err(DiscordRPCError::SerdeJson(_))));
+}
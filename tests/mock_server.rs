@@ -0,0 +1,754 @@
+use discord_ipc_rust::models::receive::{
+    ReceivedItem,
+    codes::{RpcCloseCode, RpcErrorCode},
+    commands::ReturnedCommand,
+    events::{GuildStatusData, ReturnedEvent, SpeakingData},
+};
+use discord_ipc_rust::models::send::{
+    ActivityBuilder,
+    commands::{GetChannelsArgs, SentCommand, SetVoiceSettingsArgs},
+    events::SubscribeableEvent,
+};
+use discord_ipc_rust::models::shared::{Guild, Scope, User};
+use discord_ipc_rust::testing::{MockDiscordServer, MockReply};
+use discord_ipc_rust::{
+    DEFAULT_MAX_FRAME_SIZE, DiscordIpcClient, DiscordRPCError, EndpointSource, FrameCodec, OpCode,
+    ReconnectConfig, Subscription, TimeoutPhase, discover_endpoints,
+};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+
+async fn connect(server: &MockDiscordServer) -> (DiscordIpcClient, User) {
+    DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .connect()
+        .await
+        .unwrap()
+}
+
+async fn eventually(condition: impl Fn() -> bool) {
+    for _ in 0..100 {
+        if condition() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("condition was not met in time");
+}
+
+#[tokio::test]
+async fn handshake_returns_ready_user() {
+    let server = MockDiscordServer::start().await.unwrap();
+
+    let (client, user) = connect(&server).await;
+
+    assert_eq!(client.client_id, "1234");
+    assert_eq!(user.username, "mock_user");
+}
+
+#[tokio::test]
+async fn request_returns_scripted_response() {
+    let server = MockDiscordServer::start().await.unwrap();
+    server.on_command("GET_GUILDS", |_| {
+        MockReply::Data(json!({ "guilds": [{ "id": "1", "name": "Guild", "icon_url": null }] }))
+    });
+    let (client, _) = connect(&server).await;
+
+    let response = client.request(SentCommand::GetGuilds).await.unwrap();
+
+    match response {
+        ReturnedCommand::GetGuilds { guilds } => assert_eq!(guilds[0].name, "Guild"),
+        other => panic!("unexpected response {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn request_returns_rpc_error() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+
+    let command = SentCommand::GetChannels(GetChannelsArgs {
+        guild_id: "1".to_owned(),
+    });
+    let error = client.request(command).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        DiscordRPCError::Rpc {
+            code: RpcErrorCode::InvalidCommand,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn set_and_clear_activity() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+
+    let activity = ActivityBuilder::new().state("In a match").build().unwrap();
+    let data = client.set_activity(activity).await.unwrap().unwrap();
+    client.clear_activity().await.unwrap();
+
+    assert_eq!(data.state.as_deref(), Some("In a match"));
+    let commands = server.received_commands();
+    assert_eq!(commands[1]["cmd"], "SET_ACTIVITY");
+    assert!(commands[1]["args"]["activity"].is_null());
+}
+
+#[tokio::test]
+async fn subscription_yields_events_and_unsubscribes_on_drop() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+    let event = SubscribeableEvent::SpeakingStart {
+        channel_id: "5".to_owned(),
+    };
+
+    let mut first: Subscription<SpeakingData> = client.subscribe(event.clone()).await.unwrap();
+    let second: Subscription<SpeakingData> = client.subscribe(event).await.unwrap();
+    server.dispatch(&ReturnedEvent::SpeakingStop(SpeakingData {
+        user_id: "6".to_owned(),
+    }));
+    server.dispatch(&ReturnedEvent::SpeakingStart(SpeakingData {
+        user_id: "7".to_owned(),
+    }));
+
+    assert_eq!(first.recv().await.unwrap().unwrap().user_id, "7");
+    drop(first);
+    drop(second);
+    eventually(|| server.received_commands().len() == 2).await;
+    let commands = server.received_commands();
+    assert_eq!(commands[0]["cmd"], "SUBSCRIBE");
+    assert_eq!(commands[1]["cmd"], "UNSUBSCRIBE");
+}
+
+#[tokio::test]
+async fn subscriptions_only_yield_events_for_their_arguments() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+    let guild_status = |guild_id: &str| SubscribeableEvent::GuildStatus {
+        guild_id: guild_id.to_owned(),
+    };
+    let dispatch = |guild_id: &str| {
+        server.dispatch(&ReturnedEvent::GuildStatus(GuildStatusData {
+            guild: Guild {
+                id: guild_id.to_owned(),
+                name: "Guild".to_owned(),
+                icon_url: None,
+            },
+            online: 0,
+        }))
+    };
+
+    let mut first: Subscription<GuildStatusData> =
+        client.subscribe(guild_status("1")).await.unwrap();
+    let mut second: Subscription<GuildStatusData> =
+        client.subscribe(guild_status("2")).await.unwrap();
+    dispatch("2");
+    dispatch("1");
+
+    assert_eq!(first.recv().await.unwrap().unwrap().guild.id, "1");
+    assert_eq!(second.recv().await.unwrap().unwrap().guild.id, "2");
+
+    let speaking = |channel_id: &str| SubscribeableEvent::SpeakingStart {
+        channel_id: channel_id.to_owned(),
+    };
+    let _speaking: Subscription<SpeakingData> = client.subscribe(speaking("1")).await.unwrap();
+    let conflict = client.subscribe::<SpeakingData>(speaking("2")).await;
+    assert!(matches!(
+        conflict,
+        Err(DiscordRPCError::SubscriptionConflict(event)) if event == "SPEAKING_START"
+    ));
+}
+
+#[tokio::test]
+async fn resubscribing_after_dropping_the_last_handle_stays_subscribed() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+
+    for _ in 0..3 {
+        let subscription = client
+            .subscribe::<ReturnedEvent>(SubscribeableEvent::VoiceChannelSelect)
+            .await
+            .unwrap();
+        drop(subscription);
+    }
+    let _subscription = client
+        .subscribe::<ReturnedEvent>(SubscribeableEvent::VoiceChannelSelect)
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let commands: Vec<_> = server
+        .received_commands()
+        .iter()
+        .map(|command| command["cmd"].as_str().unwrap().to_owned())
+        .collect();
+    let expected = ["SUBSCRIBE", "UNSUBSCRIBE"].into_iter().cycle();
+    assert!(
+        commands
+            .iter()
+            .zip(expected)
+            .all(|(cmd, expected)| cmd == expected)
+    );
+    assert_eq!(commands.last().unwrap(), "SUBSCRIBE");
+}
+
+#[test]
+fn subscription_can_be_dropped_outside_the_runtime() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let (server, client, subscription) = runtime.block_on(async {
+        let server = MockDiscordServer::start().await.unwrap();
+        let (client, _) = connect(&server).await;
+        let subscription = client
+            .subscribe::<ReturnedEvent>(SubscribeableEvent::VoiceChannelSelect)
+            .await
+            .unwrap();
+        (server, client, subscription)
+    });
+
+    drop(subscription);
+
+    runtime.block_on(async {
+        let _subscription = client
+            .subscribe::<ReturnedEvent>(SubscribeableEvent::VoiceChannelSelect)
+            .await
+            .unwrap();
+        assert_eq!(server.received_commands().len(), 1);
+    });
+}
+
+#[tokio::test]
+async fn event_handler_receives_events_but_not_responses() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+    let (sender, mut received) = tokio::sync::mpsc::unbounded_channel();
+    client.setup_event_handler(move |item| {
+        let _ = sender.send(item);
+    });
+
+    let data = tokio::time::timeout(
+        Duration::from_secs(1),
+        client.authenticate("token".to_owned()),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(data.user.username, "mock_user");
+    server.dispatch(&ReturnedEvent::SpeakingStart(SpeakingData {
+        user_id: "7".to_owned(),
+    }));
+
+    match received.recv().await.unwrap() {
+        ReceivedItem::Event(event) => assert!(matches!(*event, ReturnedEvent::SpeakingStart(_))),
+        other => panic!("unexpected item {:?}", other),
+    }
+    assert!(received.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn ping_is_answered_with_pong() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (_client, _) = connect(&server).await;
+
+    server.send_frame(OpCode::Ping, "{\"ping\":1}");
+
+    eventually(|| {
+        server
+            .received_frames()
+            .contains(&(OpCode::Pong, "{\"ping\":1}".to_owned()))
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn close_frame_is_surfaced_with_reason() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+    let mut events = client.events();
+
+    server.close(RpcCloseCode::RateLimited, "Slow down");
+
+    match events.recv().await.unwrap() {
+        ReceivedItem::Closed(data) => {
+            assert_eq!(data.code, RpcCloseCode::RateLimited);
+            assert_eq!(data.message, "Slow down");
+        }
+        other => panic!("unexpected item {:?}", other),
+    }
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        ReceivedItem::SocketClosed
+    ));
+}
+
+#[tokio::test]
+async fn streams_that_fall_behind_report_how_many_values_they_skipped() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+    let received = Arc::new(AtomicUsize::new(0));
+    let counter = received.clone();
+    client.setup_event_handler(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    let event = SubscribeableEvent::GuildStatus {
+        guild_id: "1".to_owned(),
+    };
+    let mut events = client.events();
+    let mut subscription: Subscription<GuildStatusData> = client.subscribe(event).await.unwrap();
+
+    for online in 0..300 {
+        server.dispatch(&ReturnedEvent::GuildStatus(GuildStatusData {
+            guild: Guild {
+                id: "1".to_owned(),
+                name: "Guild".to_owned(),
+                icon_url: None,
+            },
+            online,
+        }));
+    }
+    eventually(|| received.load(Ordering::SeqCst) == 300).await;
+
+    assert!(matches!(
+        events.recv().await,
+        Some(ReceivedItem::Lagged(44))
+    ));
+    assert!(matches!(
+        subscription.recv().await,
+        Some(Err(DiscordRPCError::Lagged(44)))
+    ));
+    assert_eq!(subscription.recv().await.unwrap().unwrap().online, 44);
+}
+
+#[tokio::test]
+async fn reconnecting_restores_the_session_until_attempts_run_out() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .reconnect(ReconnectConfig {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            multiplier: 1.0,
+            max_attempts: Some(2),
+        })
+        .connect()
+        .await
+        .unwrap();
+    let mut events = client.events();
+    client.authenticate("token".to_owned()).await.unwrap();
+    let _subscription = client
+        .subscribe::<ReturnedEvent>(SubscribeableEvent::VoiceChannelSelect)
+        .await
+        .unwrap();
+    let activity = ActivityBuilder::new().state("Playing").build().unwrap();
+    client.set_activity(activity).await.unwrap();
+
+    server.disconnect();
+    assert!(matches!(
+        events.recv().await,
+        Some(ReceivedItem::Reconnecting { attempt: 1 })
+    ));
+    assert!(matches!(
+        events.recv().await,
+        Some(ReceivedItem::Reconnected(user)) if user.username == "mock_user"
+    ));
+    eventually(|| server.received_commands().len() == 6).await;
+    let restored: Vec<_> = server.received_commands()[3..]
+        .iter()
+        .map(|command| command["cmd"].as_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(restored, ["AUTHENTICATE", "SUBSCRIBE", "SET_ACTIVITY"]);
+    let handshakes = server
+        .received_frames()
+        .into_iter()
+        .filter(|(opcode, _)| *opcode == OpCode::Handshake)
+        .count();
+    assert_eq!(handshakes, 2);
+
+    drop(server);
+    for attempt in 1..=2 {
+        assert!(matches!(
+            events.recv().await,
+            Some(ReceivedItem::Reconnecting { attempt: a }) if a == attempt
+        ));
+    }
+    let item = tokio::time::timeout(Duration::from_secs(1), events.recv()).await;
+    assert!(matches!(item, Ok(Some(ReceivedItem::SocketClosed))));
+}
+
+#[tokio::test]
+async fn builder_selects_instance_in_search_dir() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let name = server.path().file_name().unwrap().to_str().unwrap();
+    let instance = name.trim_start_matches("discord-ipc-").parse().unwrap();
+
+    let (client, user) = DiscordIpcClient::builder("1234")
+        .search_dir(server.path().parent().unwrap())
+        .instance(instance)
+        .connect()
+        .await
+        .unwrap();
+
+    assert_eq!(client.client_id, "1234");
+    assert_eq!(user.username, "mock_user");
+    assert_eq!(server.connection_count(), 1);
+}
+
+#[tokio::test]
+async fn mock_server_reclaims_stale_sockets() {
+    let directory = std::env::temp_dir().join(format!("discord-ipc-stale-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let stale = directory.join("discord-ipc-0");
+    drop(std::os::unix::net::UnixListener::bind(&stale).unwrap());
+    let live = std::os::unix::net::UnixListener::bind(directory.join("discord-ipc-1")).unwrap();
+
+    let server = MockDiscordServer::start_in(&directory).await.unwrap();
+    let (_client, user) = connect(&server).await;
+
+    assert_eq!(server.path(), stale);
+    assert_eq!(user.username, "mock_user");
+    drop(server);
+    drop(live);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[tokio::test]
+async fn mock_server_binds_in_a_directory_of_its_own() {
+    let first = MockDiscordServer::start().await.unwrap();
+    let second = MockDiscordServer::start().await.unwrap();
+    let directory = first.path().parent().unwrap().to_owned();
+
+    assert_ne!(directory, std::env::temp_dir());
+    assert_ne!(directory, second.path().parent().unwrap());
+    drop(first);
+    assert!(!directory.exists());
+}
+
+#[tokio::test]
+async fn discovered_endpoint_can_be_probed() {
+    let server = MockDiscordServer::start_in(std::env::temp_dir())
+        .await
+        .unwrap();
+
+    let endpoint = discover_endpoints()
+        .into_iter()
+        .find(|endpoint| endpoint.path == server.path())
+        .expect("the mock server's socket is in a default location");
+    assert_eq!(endpoint.source, EndpointSource::Tmp);
+
+    let ready = endpoint.probe("1234").await.unwrap();
+    assert_eq!(ready.user.username, "mock_user");
+    assert_eq!(ready.config.environment, "production");
+    eventually(|| server.connection_count() == 0).await;
+}
+
+#[tokio::test]
+async fn unanswered_request_times_out() {
+    let server = MockDiscordServer::start().await.unwrap();
+    server.on_command("GET_CHANNELS", |_| MockReply::None);
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .request_timeout(Some(Duration::from_millis(50)))
+        .connect()
+        .await
+        .unwrap();
+
+    let command = SentCommand::GetChannels(GetChannelsArgs {
+        guild_id: "1".to_owned(),
+    });
+    let error = client.request(command).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        DiscordRPCError::Timeout {
+            phase: TimeoutPhase::Request,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn request_times_out_while_the_server_is_not_reading() {
+    let path = std::env::temp_dir().join(format!("discord-ipc-stalled-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path).unwrap();
+    // Answers the handshake without reading it, then never reads again
+    let accept = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let ready = json!({
+            "cmd": "DISPATCH",
+            "evt": "READY",
+            "data": {
+                "v": 1,
+                "config": { "cdn_host": "", "api_endpoint": "", "environment": "production" },
+                "user": { "id": "1", "username": "mock_user", "avatar": null },
+            },
+            "nonce": null,
+        });
+        let mut frame = Vec::new();
+        FrameCodec::new(DEFAULT_MAX_FRAME_SIZE)
+            .encode(OpCode::Frame, ready.to_string().as_bytes(), &mut frame)
+            .unwrap();
+        stream.write_all(&frame).await.unwrap();
+        std::future::pending::<()>().await;
+        drop(stream);
+    });
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(&path)
+        .request_timeout(Some(Duration::from_millis(50)))
+        .connect()
+        .await
+        .unwrap();
+
+    // Far larger than the socket buffer, so sending it waits for the server to read
+    let command = SentCommand::GetChannels(GetChannelsArgs {
+        guild_id: "1".repeat(8 * 1024 * 1024),
+    });
+    let result = tokio::time::timeout(Duration::from_secs(1), client.request(command)).await;
+    accept.abort();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(matches!(
+        result,
+        Ok(Err(DiscordRPCError::Timeout {
+            phase: TimeoutPhase::Request,
+            ..
+        }))
+    ));
+}
+
+#[tokio::test]
+async fn silent_server_times_out_during_handshake() {
+    let path = std::env::temp_dir().join(format!("discord-ipc-silent-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path).unwrap();
+    let accept = tokio::spawn(async move { listener.accept().await });
+
+    let result = DiscordIpcClient::builder("1234")
+        .pipe_path(&path)
+        .handshake_timeout(Some(Duration::from_millis(50)))
+        .connect()
+        .await;
+    accept.abort();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(matches!(
+        result,
+        Err(DiscordRPCError::Timeout {
+            phase: TimeoutPhase::Handshake,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn oversized_frame_drops_the_connection() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .max_frame_size(1024)
+        .connect()
+        .await
+        .unwrap();
+    let mut events = client.events();
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&(OpCode::Frame as u32).to_le_bytes());
+    header.extend_from_slice(&u32::MAX.to_le_bytes());
+    server.send_bytes(&header);
+
+    let item = tokio::time::timeout(Duration::from_secs(1), events.recv()).await;
+    assert!(matches!(item, Ok(Some(ReceivedItem::SocketClosed))));
+}
+
+#[tokio::test]
+async fn concurrent_requests_receive_their_own_responses() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+
+    let set = |state: &str| {
+        let activity = ActivityBuilder::new().state(state).build().unwrap();
+        client.set_activity(activity)
+    };
+    let (first, second, third, fourth) =
+        tokio::join!(set("First"), set("Second"), set("Third"), set("Fourth"));
+
+    for (response, state) in [
+        (first, "First"),
+        (second, "Second"),
+        (third, "Third"),
+        (fourth, "Fourth"),
+    ] {
+        assert_eq!(response.unwrap().unwrap().state.as_deref(), Some(state));
+    }
+    assert_eq!(server.received_commands().len(), 4);
+}
+
+#[tokio::test]
+async fn clones_share_one_connection_across_tasks() {
+    fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+    assert_shareable::<DiscordIpcClient>();
+
+    let server = MockDiscordServer::start().await.unwrap();
+    server.on_command("GET_GUILDS", |_| MockReply::Data(json!({ "guilds": [] })));
+    let (client, _) = connect(&server).await;
+
+    let tasks: Vec<_> = (0..4)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.request(SentCommand::GetGuilds).await })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+    drop(client);
+
+    assert_eq!(server.received_commands().len(), 4);
+}
+
+#[tokio::test]
+async fn dropping_the_client_ends_subscriptions_and_closes_the_connection() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+    let mut subscription = client
+        .subscribe::<ReturnedEvent>(SubscribeableEvent::VoiceChannelSelect)
+        .await
+        .unwrap();
+
+    drop(client);
+
+    let payload = tokio::time::timeout(Duration::from_secs(1), subscription.recv()).await;
+    assert!(matches!(payload, Ok(None)));
+    eventually(|| server.connection_count() == 0).await;
+    drop(subscription);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(server.received_commands().len(), 1);
+}
+
+#[tokio::test]
+async fn close_clears_activity_and_sends_close_frame() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .clear_activity_on_close(true)
+        .connect()
+        .await
+        .unwrap();
+    let clone = client.clone();
+    let mut events = client.events();
+    let activity = ActivityBuilder::new().state("Playing").build().unwrap();
+    client.set_activity(activity).await.unwrap();
+
+    tokio::time::timeout(Duration::from_secs(1), client.close())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let frames = server.received_frames();
+    let commands = server.received_commands();
+    assert_eq!(commands.last().unwrap()["args"]["activity"], json!(null));
+    let (opcode, payload) = frames.last().unwrap();
+    assert_eq!(*opcode, OpCode::Close);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(payload).unwrap()["code"],
+        1000
+    );
+    assert!(matches!(
+        events.recv().await,
+        Some(ReceivedItem::SocketClosed)
+    ));
+    assert!(matches!(
+        clone.request(SentCommand::GetGuilds).await,
+        Err(DiscordRPCError::ConnectionClosed)
+    ));
+}
+
+#[tokio::test]
+async fn close_does_not_wait_longer_than_the_close_timeout_to_clear_the_activity() {
+    let server = MockDiscordServer::start().await.unwrap();
+    server.on_command(
+        "SET_ACTIVITY",
+        |command| match &command["args"]["activity"] {
+            serde_json::Value::Null => MockReply::None,
+            activity => MockReply::Data(activity.clone()),
+        },
+    );
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .clear_activity_on_close(true)
+        .close_timeout(Some(Duration::from_millis(50)))
+        .connect()
+        .await
+        .unwrap();
+    let activity = ActivityBuilder::new().state("Playing").build().unwrap();
+    client.set_activity(activity).await.unwrap();
+
+    let result = tokio::time::timeout(Duration::from_secs(1), client.close())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        result,
+        Err(DiscordRPCError::Timeout {
+            phase: TimeoutPhase::Request,
+            ..
+        })
+    ));
+    assert_eq!(server.received_frames().last().unwrap().0, OpCode::Close);
+    eventually(|| server.connection_count() == 0).await;
+}
+
+#[tokio::test]
+async fn commands_without_granted_scopes_are_rejected_before_sending() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = connect(&server).await;
+    let set_voice_settings = || SentCommand::SetVoiceSettings(SetVoiceSettingsArgs::default());
+
+    // The granted scopes are unknown before authenticating, so nothing is checked
+    let error = client.request(set_voice_settings()).await.unwrap_err();
+    assert!(matches!(error, DiscordRPCError::Rpc { .. }));
+
+    client.authenticate("token".to_owned()).await.unwrap();
+    assert_eq!(
+        client.granted_scopes(),
+        Some(vec![Scope::Rpc, Scope::Identify])
+    );
+    let sent = server.received_commands().len();
+
+    let error = client.request(set_voice_settings()).await.unwrap_err();
+    assert!(matches!(
+        error,
+        DiscordRPCError::MissingScope { command, missing }
+            if command == "SET_VOICE_SETTINGS" && missing == [Scope::RpcVoiceWrite]
+    ));
+    let subscription = client
+        .subscribe::<ReturnedEvent>(SubscribeableEvent::NotificationCreate)
+        .await;
+    assert!(matches!(
+        subscription,
+        Err(DiscordRPCError::MissingScope { .. })
+    ));
+    assert_eq!(server.received_commands().len(), sent);
+}
+
+#[tokio::test]
+async fn scope_checking_can_be_disabled() {
+    let server = MockDiscordServer::start().await.unwrap();
+    server.on_command("SET_VOICE_SETTINGS", |_| MockReply::Data(json!({})));
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .check_scopes(false)
+        .connect()
+        .await
+        .unwrap();
+    client.authenticate("token".to_owned()).await.unwrap();
+
+    let command = SentCommand::SetVoiceSettings(SetVoiceSettingsArgs::default());
+    client.request(command).await.unwrap();
+}
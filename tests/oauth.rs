@@ -0,0 +1,417 @@
+use discord_ipc_rust::models::receive::codes::RpcErrorCode;
+use discord_ipc_rust::models::shared::Scope;
+use discord_ipc_rust::oauth::{
+    HttpTokenExchange, JsonFileTokenStore, Pkce, StoredToken, TokenExchange, TokenGrant,
+    TokenRequest, TokenResponse, TokenStore,
+};
+use discord_ipc_rust::testing::{MockDiscordServer, MockReply};
+use discord_ipc_rust::transport::BoxFuture;
+use discord_ipc_rust::{DiscordIpcClient, DiscordRPCError, Result};
+
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, SystemTime};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Stands in for the token endpoint, recording every request
+#[derive(Clone, Default)]
+struct RecordingExchange {
+    requests: Arc<Mutex<Vec<TokenRequest>>>,
+}
+
+impl TokenExchange for RecordingExchange {
+    fn exchange<'a>(&'a self, request: &'a TokenRequest) -> BoxFuture<'a, Result<TokenResponse>> {
+        self.requests.lock().unwrap().push(request.clone());
+        // Authorization codes yield a token that expires within a minute, so that it is refreshed right away
+        let (access_token, expires_in) = match request.grant {
+            TokenGrant::AuthorizationCode { .. } => ("mock_access_token", 60),
+            TokenGrant::RefreshToken { .. } => ("refreshed_access_token", 604800),
+        };
+        Box::pin(async move {
+            Ok(TokenResponse {
+                access_token: access_token.to_owned(),
+                token_type: "Bearer".to_owned(),
+                expires_in,
+                refresh_token: Some("mock_refresh_token".to_owned()),
+                scope: "rpc identify".to_owned(),
+            })
+        })
+    }
+}
+
+/// Keeps the token in memory
+#[derive(Clone, Default)]
+struct MemoryStore {
+    token: Arc<Mutex<Option<StoredToken>>>,
+}
+
+impl TokenStore for MemoryStore {
+    fn load(&self) -> Result<Option<StoredToken>> {
+        Ok(self.token.lock().unwrap().clone())
+    }
+
+    fn save(&self, token: &StoredToken) -> Result<()> {
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.token.lock().unwrap().take();
+        Ok(())
+    }
+}
+
+/// Fails to save every token
+struct FailingStore;
+
+impl TokenStore for FailingStore {
+    fn load(&self) -> Result<Option<StoredToken>> {
+        Ok(None)
+    }
+
+    fn save(&self, _token: &StoredToken) -> Result<()> {
+        Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied).into())
+    }
+
+    fn clear(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn authenticated_tokens(server: &MockDiscordServer) -> Vec<String> {
+    server
+        .received_commands()
+        .into_iter()
+        .filter(|command| command["cmd"] == "AUTHENTICATE")
+        .map(|command| command["args"]["access_token"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+#[tokio::test]
+async fn authorize_and_authenticate_exchanges_the_code() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let exchange = RecordingExchange::default();
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .token_exchange(exchange.clone())
+        .connect()
+        .await
+        .unwrap();
+
+    let (data, token) = client
+        .authorize_and_authenticate(
+            vec![Scope::Rpc, Scope::Identify],
+            Some("secret"),
+            "http://localhost",
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(data.user.username, "mock_user");
+    assert_eq!(
+        data.expires,
+        SystemTime::UNIX_EPOCH + Duration::from_secs(4_070_908_800)
+    );
+    assert_eq!(token.access_token, "mock_access_token");
+    let requests = exchange.requests.lock().unwrap();
+    assert!(matches!(
+        &requests[0].grant,
+        TokenGrant::AuthorizationCode { code, .. } if code == "mock_authorization_code"
+    ));
+    let commands = server.received_commands();
+    assert_eq!(commands[1]["cmd"], "AUTHENTICATE");
+    assert_eq!(commands[1]["args"]["access_token"], "mock_access_token");
+}
+
+#[tokio::test]
+async fn public_clients_authorize_with_pkce() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let exchange = RecordingExchange::default();
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .token_exchange(exchange.clone())
+        .connect()
+        .await
+        .unwrap();
+
+    client
+        .authorize_and_authenticate(vec![Scope::Rpc], None, "http://localhost")
+        .await
+        .unwrap();
+
+    let authorize = &server.received_commands()[0]["args"];
+    assert_eq!(authorize["code_challenge_method"], "S256");
+    let requests = exchange.requests.lock().unwrap();
+    assert!(requests[0].client_secret.is_none());
+    let TokenGrant::AuthorizationCode {
+        code_verifier: Some(code_verifier),
+        ..
+    } = &requests[0].grant
+    else {
+        panic!("unexpected grant {:?}", requests[0].grant);
+    };
+    assert_eq!(
+        authorize["code_challenge"],
+        Pkce::from_verifier(code_verifier.as_str())
+            .unwrap()
+            .challenge()
+    );
+    assert!(
+        !requests[0]
+            .form()
+            .iter()
+            .any(|(name, _)| *name == "client_secret")
+    );
+}
+
+#[test]
+fn pkce_challenge_is_the_encoded_sha256_of_the_verifier() {
+    let pkce = Pkce::from_verifier("dBjftJeZ4CVP-mJ92IXmJ4W3P6sl0o3QqSs1pahJ5Pw").unwrap();
+
+    assert_eq!(
+        pkce.challenge(),
+        "qRHA1eKAhLNlAX8RCKFlYu9DUim7ujNo3prbb4mkS8k"
+    );
+    assert!((43..=128).contains(&Pkce::new().verifier().len()));
+}
+
+#[test]
+fn pkce_rejects_invalid_code_verifiers() {
+    assert!(Pkce::from_verifier("a".repeat(43)).is_ok());
+    assert!(Pkce::from_verifier("a".repeat(128)).is_ok());
+    assert!(Pkce::from_verifier(format!("{}-._~", "A0".repeat(20))).is_ok());
+
+    for verifier in [
+        "a".repeat(42),
+        "a".repeat(129),
+        format!("{}+", "a".repeat(42)),
+        format!("{} ", "a".repeat(42)),
+        format!("{}é", "a".repeat(42)),
+    ] {
+        assert!(matches!(
+            Pkce::from_verifier(verifier.clone()),
+            Err(DiscordRPCError::InvalidCodeVerifier(invalid)) if invalid == verifier
+        ));
+    }
+}
+
+#[tokio::test]
+async fn http_exchange_reports_endpoint_errors() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/oauth2/token", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = [0u8; 4096];
+        let _ = stream.read(&mut request).await.unwrap();
+        let body =
+            r#"{"error": "invalid_grant", "error_description": "Invalid \"code\" in request."}"#;
+        let response = format!(
+            "HTTP/1.1 400 Bad Request\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    let request = TokenRequest {
+        client_id: "1234".to_owned(),
+        client_secret: Some("secret".to_owned()),
+        grant: TokenGrant::AuthorizationCode {
+            code: "expired".to_owned(),
+            redirect_uri: "http://localhost".to_owned(),
+            code_verifier: None,
+        },
+    };
+    let error = HttpTokenExchange::new(url)
+        .exchange(&request)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        DiscordRPCError::TokenExchange(message) if message == r#"invalid_grant: Invalid "code" in request."#
+    ));
+}
+
+#[test]
+fn http_exchange_fails_outside_of_a_tokio_runtime() {
+    let request = TokenRequest {
+        client_id: "1234".to_owned(),
+        client_secret: None,
+        grant: TokenGrant::RefreshToken {
+            refresh_token: "refresh_token".to_owned(),
+        },
+    };
+    let exchange = HttpTokenExchange::default();
+
+    let mut future = exchange.exchange(&request);
+    let poll = future
+        .as_mut()
+        .poll(&mut Context::from_waker(Waker::noop()));
+
+    assert!(matches!(
+        poll,
+        Poll::Ready(Err(DiscordRPCError::TokenExchange(_)))
+    ));
+}
+
+#[tokio::test]
+async fn failing_to_save_the_token_is_returned() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .token_exchange(RecordingExchange::default())
+        .token_store(FailingStore)
+        .refresh_margin(Duration::ZERO)
+        .connect()
+        .await
+        .unwrap();
+
+    let result = client
+        .authorize_and_authenticate(vec![Scope::Rpc], Some("secret"), "http://localhost")
+        .await;
+
+    assert!(
+        matches!(result, Err(DiscordRPCError::Io(error)) if error.kind() == std::io::ErrorKind::PermissionDenied)
+    );
+    assert_eq!(authenticated_tokens(&server), ["mock_access_token"]);
+}
+
+#[tokio::test]
+async fn token_is_saved_and_refreshed_before_it_expires() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let exchange = RecordingExchange::default();
+    let store = MemoryStore::default();
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .token_exchange(exchange.clone())
+        .token_store(store.clone())
+        .refresh_margin(Duration::from_secs(120))
+        .connect()
+        .await
+        .unwrap();
+
+    client
+        .authorize_and_authenticate(vec![Scope::Rpc], Some("secret"), "http://localhost")
+        .await
+        .unwrap();
+
+    for _ in 0..100 {
+        if authenticated_tokens(&server).len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(
+        authenticated_tokens(&server),
+        ["mock_access_token", "refreshed_access_token"]
+    );
+    let saved = store.load().unwrap().unwrap();
+    assert_eq!(saved.access_token, "refreshed_access_token");
+    assert_eq!(saved.refresh_token.as_deref(), Some("mock_refresh_token"));
+    assert!(matches!(
+        &exchange.requests.lock().unwrap()[1].grant,
+        TokenGrant::RefreshToken { refresh_token } if refresh_token == "mock_refresh_token"
+    ));
+}
+
+#[tokio::test]
+async fn refreshed_token_is_saved_even_if_authenticating_fails() {
+    let store = MemoryStore::default();
+    store
+        .save(&StoredToken {
+            access_token: "old_access".to_owned(),
+            refresh_token: Some("old_refresh".to_owned()),
+            expires_at: 0,
+            scopes: vec![Scope::Rpc],
+        })
+        .unwrap();
+    let server = MockDiscordServer::start().await.unwrap();
+    server.on_command("AUTHENTICATE", |_| MockReply::Error {
+        code: RpcErrorCode::InvalidToken,
+        message: "Invalid token".to_owned(),
+    });
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .token_exchange(RecordingExchange::default())
+        .token_store(store.clone())
+        .connect()
+        .await
+        .unwrap();
+
+    let result = client.authenticate_with_stored_token(Some("secret")).await;
+
+    assert!(matches!(result, Err(DiscordRPCError::Rpc { .. })));
+    let saved = store.load().unwrap().unwrap();
+    assert_eq!(saved.access_token, "refreshed_access_token");
+    assert_eq!(saved.refresh_token.as_deref(), Some("mock_refresh_token"));
+}
+
+#[tokio::test]
+async fn token_refreshed_on_demand_is_saved_even_if_authenticating_fails() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let store = MemoryStore::default();
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .token_exchange(RecordingExchange::default())
+        .token_store(store.clone())
+        .refresh_margin(Duration::ZERO)
+        .connect()
+        .await
+        .unwrap();
+    client
+        .authorize_and_authenticate(vec![Scope::Rpc], Some("secret"), "http://localhost")
+        .await
+        .unwrap();
+    server.on_command("AUTHENTICATE", |_| MockReply::Error {
+        code: RpcErrorCode::InvalidToken,
+        message: "Invalid token".to_owned(),
+    });
+
+    let result = client.refresh_access_token().await;
+
+    assert!(matches!(result, Err(DiscordRPCError::Rpc { .. })));
+    let saved = store.load().unwrap().unwrap();
+    assert_eq!(saved.access_token, "refreshed_access_token");
+}
+
+#[tokio::test]
+async fn stored_token_is_loaded_from_a_json_file() {
+    let path = std::env::temp_dir().join(format!("discord-ipc-rust-{}.json", std::process::id()));
+    let store = JsonFileTokenStore::new(&path);
+    store
+        .save(&StoredToken {
+            access_token: "stored_access_token".to_owned(),
+            refresh_token: None,
+            expires_at: u64::MAX / 2,
+            scopes: vec![Scope::Rpc],
+        })
+        .unwrap();
+
+    let server = MockDiscordServer::start().await.unwrap();
+    let (client, _) = DiscordIpcClient::builder("1234")
+        .pipe_path(server.path())
+        .token_exchange(RecordingExchange::default())
+        .token_store(store.clone())
+        .connect()
+        .await
+        .unwrap();
+    let data = client
+        .authenticate_with_stored_token(Some("secret"))
+        .await
+        .unwrap();
+
+    assert!(data.is_some());
+    assert_eq!(authenticated_tokens(&server), ["stored_access_token"]);
+    store.clear().unwrap();
+    assert!(store.load().unwrap().is_none());
+    assert!(
+        client
+            .authenticate_with_stored_token(Some("secret"))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
@@ -0,0 +1,42 @@
+use discord_ipc_rust::models::send::{
+    commands::{SentCommand, SetVoiceSettingsArgs},
+    events::SubscribeableEvent,
+};
+use discord_ipc_rust::models::shared::Scope;
+
+use serde_json::json;
+
+#[test]
+fn scopes_round_trip_through_their_names() {
+    let scopes: Vec<Scope> =
+        serde_json::from_value(json!(["rpc.voice.read", "identify", "rpc.voice.reed"])).unwrap();
+
+    assert_eq!(
+        scopes,
+        [
+            Scope::RpcVoiceRead,
+            Scope::Identify,
+            Scope::Other("rpc.voice.reed".to_owned())
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(&scopes).unwrap(),
+        json!(["rpc.voice.read", "identify", "rpc.voice.reed"])
+    );
+}
+
+#[test]
+fn commands_report_missing_scopes() {
+    let granted = [Scope::Rpc, Scope::RpcVoiceRead];
+    let set_voice_settings = SentCommand::SetVoiceSettings(SetVoiceSettingsArgs::default());
+
+    assert_eq!(
+        set_voice_settings.missing_scopes(&granted),
+        [Scope::RpcVoiceWrite]
+    );
+    assert!(SentCommand::GetVoiceSettings.is_permitted_by(&granted));
+    assert!(
+        !SentCommand::Subscribe(SubscribeableEvent::NotificationCreate).is_permitted_by(&granted)
+    );
+    assert!(SentCommand::Subscribe(SubscribeableEvent::ActivityJoin).is_permitted_by(&[]));
+}
@@ -0,0 +1,66 @@
+use discord_ipc_rust::models::send::ActivityBuilder;
+use discord_ipc_rust::testing::MockDiscordServer;
+use discord_ipc_rust::transport::{AsyncStdTransport, SmolTransport, Transport};
+use discord_ipc_rust::{DiscordIpcClient, Endpoint, EndpointSource};
+
+use std::path::PathBuf;
+
+// The mock server runs on tokio, so each client runs on its own runtime in a separate thread
+async fn set_activity(path: PathBuf, transport: impl Transport) -> Option<String> {
+    let (client, user) = DiscordIpcClient::builder("1234")
+        .pipe_path(path)
+        .transport(transport)
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(user.username, "mock_user");
+
+    let activity = ActivityBuilder::new().state("Testing").build().unwrap();
+    client.set_activity(activity).await.unwrap().unwrap().state
+}
+
+#[tokio::test]
+async fn smol_transport() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let path = server.path().to_path_buf();
+
+    let state =
+        tokio::task::spawn_blocking(move || smol::block_on(set_activity(path, SmolTransport)))
+            .await
+            .unwrap();
+
+    assert_eq!(state.as_deref(), Some("Testing"));
+}
+
+#[tokio::test]
+async fn async_std_transport() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let path = server.path().to_path_buf();
+
+    let state = tokio::task::spawn_blocking(move || {
+        async_std::task::block_on(set_activity(path, AsyncStdTransport))
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(state.as_deref(), Some("Testing"));
+}
+
+#[tokio::test]
+async fn endpoints_can_be_probed_with_another_transport() {
+    let server = MockDiscordServer::start().await.unwrap();
+    let endpoint = Endpoint {
+        path: server.path().to_path_buf(),
+        index: 0,
+        source: EndpointSource::Tmp,
+    };
+
+    let ready = tokio::task::spawn_blocking(move || {
+        smol::block_on(endpoint.probe_with("1234", &SmolTransport))
+    })
+    .await
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(ready.user.username, "mock_user");
+}